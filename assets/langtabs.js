@@ -1,13 +1,106 @@
+// Settings used for keys a page's config element doesn't set.
+// The preprocessor emits the same keys from `book.toml`.
+const LANGTABS_DEFAULTS = {
+    defaultNamespace: 'lang',
+    storagePrefix: 'langtabs-',
+    defaultTab: null,
+    aliases: {},
+    queryParams: {},
+    updateUrl: true,
+    fallbacks: {},
+    fallbackNote: 'Not available in {preferred}, showing {fallback}',
+    names: {},
+    switcher: false,
+    switcherShortcut: 'l',
+    languages: [],
+    languageFilter: 'none',
+    icons: 'cdn',
+    cdnUrl: 'https://cdn.jsdelivr.net/gh/devicons/devicon@v2.16.0/devicon.min.css',
+    cdnIntegrity: null,
+    nonce: null,
+    style: {},
+    themeStyles: {}
+};
+
 (function loadDeviconCSS() {
+    // Pages mdBook renders without a chapter, such as 404.html, have no settings
+    // and no tabs, so they shouldn't reach out to a CDN the book may have turned off
+    if (!getConfigElement()) {
+        return;
+    }
+
+    const config = getLangTabsConfig();
+
+    // Bundled icons are inlined into the page, so only Devicon's CDN needs loading
+    if (config.icons !== 'cdn') {
+        return;
+    }
+
     const link = document.createElement('link');
     link.rel = 'stylesheet';
-    link.href = 'https://cdn.jsdelivr.net/gh/devicons/devicon@v2.16.0/devicon.min.css';
+    link.href = config.cdnUrl;
+    link.crossOrigin = 'anonymous';
+    if (config.cdnIntegrity) {
+        link.integrity = config.cdnIntegrity;
+    }
+    if (config.nonce) {
+        link.nonce = config.nonce;
+    }
     document.head.appendChild(link);
 })();
 
+// Apply `[preprocessor.langtabs.style]` through the CSSOM, which a
+// Content-Security-Policy without 'unsafe-inline' still allows
+(function applyStyle() {
+    if (!getConfigElement()) {
+        return;
+    }
+
+    const config = getLangTabsConfig();
+    const rules = [[':root', config.style]];
+    Object.keys(config.themeStyles).forEach(function(theme) {
+        // mdBook sets the theme as a class on the html element
+        rules.push([`html.${theme}`, config.themeStyles[theme]]);
+    });
+    if (rules.every(function(rule) { return Object.keys(rule[1]).length === 0; })) {
+        return;
+    }
+
+    let sheet;
+    if (document.adoptedStyleSheets && typeof CSSStyleSheet === 'function') {
+        sheet = new CSSStyleSheet();
+        document.adoptedStyleSheets = document.adoptedStyleSheets.concat(sheet);
+    } else {
+        const element = document.createElement('style');
+        if (config.nonce) {
+            element.nonce = config.nonce;
+        }
+        document.head.appendChild(element);
+        sheet = element.sheet;
+    }
+
+    rules.forEach(function(rule) {
+        const index = sheet.insertRule(`${rule[0]} {}`, sheet.cssRules.length);
+        const style = sheet.cssRules[index].style;
+        Object.keys(rule[1]).forEach(function(name) {
+            style.setProperty(`--langtabs-${name}`, rule[1][name]);
+        });
+    });
+})();
+
 document.addEventListener('DOMContentLoaded', function() {
+    applyQueryParams();
+    fixIconPaths();
     initLangTabs();
-    
+    // Only once: `?highlight=` stays in the URL while the reader switches tabs
+    selectSearchMatches();
+    initSwitcher();
+    initLanguageFilter();
+    initMoreMenus();
+    initViews();
+    updateOnlyBlocks();
+    updateTerms();
+
     // Listen for theme changes to re-style tabs
     const observer = new MutationObserver(function(mutations) {
         mutations.forEach(function(mutation) {
@@ -16,62 +109,881 @@ document.addEventListener('DOMContentLoaded', function() {
             }
         });
     });
-    
+
     observer.observe(document.documentElement, {
         attributes: true
     });
-    
+
     // Also handle theme changes when page hash changes (mdbook sometimes updates theme this way)
     window.addEventListener('hashchange', function() {
         setTimeout(initLangTabs, 100);
     });
-    
+
     // Handle page navigation
     window.addEventListener('load', function() {
         setTimeout(initLangTabs, 100);
     });
 });
 
+// The JSON script element with the settings, emitted by the preprocessor. The
+// print page has one per chapter, all alike, so it is a class rather than an id.
+function getConfigElement() {
+    return document.querySelector('script.langtabs-config');
+}
+
+// Settings emitted by the preprocessor into a JSON script element, over the defaults
+function getLangTabsConfig() {
+    if (!getLangTabsConfig.cache) {
+        let config = {};
+        const element = getConfigElement();
+        if (element) {
+            try {
+                config = JSON.parse(element.textContent);
+            } catch (e) {
+                console.warn('langtabs: invalid configuration', e);
+            }
+        }
+        getLangTabsConfig.cache = Object.assign({}, LANGTABS_DEFAULTS, config);
+    }
+    return getLangTabsConfig.cache;
+}
+
+function getDefaultNamespace() {
+    return getLangTabsConfig().defaultNamespace;
+}
+
+// localStorage key for a namespace's preference or other stored state
+function getStorageKey(name) {
+    return getLangTabsConfig().storagePrefix + name;
+}
+
+function getNamespace(container) {
+    return container.getAttribute('data-namespace') || getDefaultNamespace();
+}
+
+function getQueryParam(namespace) {
+    const queryParams = getLangTabsConfig().queryParams;
+    return queryParams[namespace] || namespace;
+}
+
+// The tab key for a language name, alias or tab label, e.g. `TS` -> `typescript`
+// or `macOS 14` -> `macos-14`, as `tab_key` does in the preprocessor
+function normalizeKey(name) {
+    const key = name.toLowerCase();
+    return getLangTabsConfig().aliases[key] ||
+        key.split(/[^\p{L}\p{N}]+/u).filter(function(part) { return part; }).join('-');
+}
+
+function getPreference(namespace) {
+    try {
+        return localStorage.getItem(getStorageKey(namespace));
+    } catch (e) {
+        return null;
+    }
+}
+
+function setPreference(namespace, key) {
+    try {
+        localStorage.setItem(getStorageKey(namespace), key);
+    } catch (e) {
+        // Storage may be unavailable, e.g. in private browsing
+    }
+}
+
+// Store preferences passed in the URL, e.g. `?lang=go&os=linux`
+function applyQueryParams() {
+    const params = new URLSearchParams(window.location.search);
+    const namespaces = new Set();
+
+    document.querySelectorAll('.langtabs').forEach(function(container) {
+        namespaces.add(getNamespace(container));
+    });
+
+    namespaces.forEach(function(namespace) {
+        const value = params.get(getQueryParam(namespace));
+        if (value) {
+            setPreference(namespace, normalizeKey(value));
+        }
+    });
+}
+
+// Reflect the selected tab in the URL so a copied link reproduces it
+function updateQueryParam(namespace, key) {
+    if (getLangTabsConfig().updateUrl === false) {
+        return;
+    }
+
+    const url = new URL(window.location.href);
+    url.searchParams.set(getQueryParam(namespace), key);
+    history.replaceState(history.state, '', url);
+}
+
 function initLangTabs() {
     const langTabsContainers = document.querySelectorAll('.langtabs');
-    
+
     langTabsContainers.forEach(function(container) {
         const tabButtons = container.querySelectorAll('.langtabs-tab');
-        
+
         tabButtons.forEach(function(button) {
             button.removeEventListener('click', handleTabClick);
             button.addEventListener('click', handleTabClick);
         });
-        
-        // If no tab active select first
+
+        // The initial tab is picked once. Later runs, e.g. after a theme change,
+        // keep whatever was selected since, such as the tab with a search hit.
+        if (container.langtabsInitialized) {
+            return;
+        }
+        container.langtabsInitialized = true;
+
+        // Select the preferred tab, or its fallback if this group lacks it
+        const preferred = getPreference(getNamespace(container));
+        if (preferred && selectPreferred(container, preferred)) {
+            return;
+        }
+
+        // OS groups default to the reader's operating system
+        if (!preferred && container.hasAttribute('data-os-group')) {
+            const osButton = container.querySelector(`.langtabs-tab[data-os="${detectOS()}"]`);
+            if (osButton) {
+                activateTab(osButton);
+                return;
+            }
+        }
+
+        // Then the tab configured as `default-tab`
+        const defaultTab = getLangTabsConfig().defaultTab;
+        const defaultButton = defaultTab && findTab(container, defaultTab);
+        if (defaultButton) {
+            activateTab(defaultButton);
+            return;
+        }
+
         if (!container.querySelector('.langtabs-tab.active')) {
+            // If no tab active select first
             const firstButton = tabButtons[0];
             if (firstButton) {
-                firstButton.click();
+                activateTab(firstButton);
+            }
+        }
+    });
+
+    applyHiddenLanguages();
+}
+
+// Words mdBook's search passes to the page it opens, e.g. `?highlight=tokio%20spawn`
+function getSearchTerms() {
+    const highlight = new URLSearchParams(window.location.search).get('highlight');
+    if (!highlight) {
+        return [];
+    }
+    return highlight.toLowerCase().split(' ').filter(function(term) {
+        return term.length > 0;
+    });
+}
+
+// Show the tab holding a search hit when the active one doesn't contain it.
+// The reader's stored preference is left alone.
+function selectSearchMatches() {
+    const terms = getSearchTerms();
+    if (terms.length === 0) {
+        return;
+    }
+
+    const countMatches = function(element) {
+        const text = element.textContent.toLowerCase();
+        return terms.filter(function(term) {
+            return text.includes(term);
+        }).length;
+    };
+
+    document.querySelectorAll('.langtabs').forEach(function(container) {
+        const active = container.querySelector('.langtabs-code.active');
+        if (active && countMatches(active) > 0) {
+            return;
+        }
+
+        let best = null;
+        let bestCount = 0;
+        container.querySelectorAll('.langtabs-tab').forEach(function(button) {
+            const panel = container.querySelector(
+                `.langtabs-code[data-lang="${button.getAttribute('data-lang')}"]`);
+            const count = panel ? countMatches(panel) : 0;
+            if (!button.hidden && count > bestCount) {
+                best = button;
+                bestCount = count;
             }
+        });
+
+        if (best) {
+            activateTab(best);
         }
     });
 }
 
+// Point icon images of nested chapters at the book root as seen from this page,
+// which differs on the print page
+function fixIconPaths() {
+    if (typeof path_to_root !== 'string') {
+        return;
+    }
+    document.querySelectorAll('img[data-root-src]').forEach(function(img) {
+        img.src = path_to_root + img.getAttribute('data-root-src');
+    });
+}
+
+// The reader's operating system as `linux`, `macos` or `windows`
+function detectOS() {
+    const platform = ((navigator.userAgentData && navigator.userAgentData.platform) ||
+        navigator.platform || navigator.userAgent).toLowerCase();
+
+    if (platform.includes('win')) {
+        return 'windows';
+    }
+    if (platform.includes('mac') || platform.includes('iphone') || platform.includes('ipad')) {
+        return 'macos';
+    }
+    if (platform.includes('linux') || platform.includes('x11') || platform.includes('bsd')) {
+        return 'linux';
+    }
+    return null;
+}
+
+// Activate the tab for `key`, following the configured fallback chain when the
+// group doesn't have it. Returns false if neither the tab nor a fallback exists.
+function selectPreferred(container, key) {
+    const fallbacks = getLangTabsConfig().fallbacks;
+    const queue = [key];
+    const visited = new Set();
+
+    while (queue.length > 0) {
+        const candidate = queue.shift();
+        if (visited.has(candidate)) {
+            continue;
+        }
+        visited.add(candidate);
+
+        const button = findTab(container, candidate);
+        if (button) {
+            activateTab(button);
+            setFallbackNote(container, candidate === key ? null : key, button);
+            return true;
+        }
+
+        queue.push(...(fallbacks[candidate] || []));
+    }
+
+    return false;
+}
+
+// Show or remove the note explaining that a fallback tab is shown
+function setFallbackNote(container, preferred, button) {
+    let note = container.querySelector('.langtabs-fallback-note');
+
+    if (!preferred) {
+        if (note) {
+            note.remove();
+        }
+        return;
+    }
+
+    if (!note) {
+        note = document.createElement('div');
+        note.className = 'langtabs-fallback-note';
+        container.querySelector('.langtabs-header').after(note);
+    }
+
+    const config = getLangTabsConfig();
+    note.textContent = config.fallbackNote
+        .replace('{preferred}', config.names[preferred] || preferred)
+        .replace('{fallback}', getTabName(button));
+}
+
+// A tab's name, leaving out the text of inline icons
+function getTabName(button) {
+    const label = button.getAttribute('aria-label');
+    if (label) {
+        return label;
+    }
+
+    return Array.from(button.childNodes)
+        .filter(function(node) {
+            return node.nodeType === Node.TEXT_NODE;
+        })
+        .map(function(node) {
+            return node.textContent;
+        })
+        .join('')
+        .trim();
+}
+
+// Find the tab for `key`, ignoring placeholders so fallbacks can take over
+// The tab for `key`, unless it is a placeholder or hidden by the language filter
+function findTab(container, key) {
+    return Array.from(container.querySelectorAll('.langtabs-tab')).find(function(button) {
+        return button.getAttribute('data-key') === key &&
+            !button.hidden &&
+            !button.classList.contains('langtabs-placeholder');
+    });
+}
+
 function handleTabClick() {
     const container = this.closest('.langtabs');
-    const lang = this.getAttribute('data-lang');
-    
+
+    // Remember where the clicked tab bar is, so resizing groups don't scroll it away
+    const header = container.querySelector('.langtabs-header');
+    const headerTop = header.getBoundingClientRect().top;
+
+    activateTab(this);
+    setFallbackNote(container, null, this);
+
+    // Placeholders only show their notice, they don't change the preference
+    if (this.classList.contains('langtabs-placeholder')) {
+        return;
+    }
+
+    selectLanguage(getNamespace(container), this.getAttribute('data-key'), container);
+    keepInPlace(header, headerTop);
+}
+
+// Scroll so `element` is back at `top` in the viewport
+function keepInPlace(element, top) {
+    const shift = element.getBoundingClientRect().top - top;
+    if (shift !== 0) {
+        window.scrollBy(0, shift);
+    }
+}
+
+// Store `key` as the preference for `namespace` and switch every group in it,
+// except `source` which already shows the right tab
+function selectLanguage(namespace, key, source) {
+    setPreference(namespace, key);
+    updateQueryParam(namespace, key);
+
+    document.querySelectorAll('.langtabs').forEach(function(container) {
+        if (container !== source && getNamespace(container) === namespace) {
+            selectPreferred(container, key);
+        }
+    });
+
+    if (namespace === getDefaultNamespace()) {
+        updateOnlyBlocks();
+        updateTerms();
+    }
+}
+
+// Show `langtabs-only` prose only to readers who prefer one of its languages.
+// Without a preference every block is shown with its language badge.
+function updateOnlyBlocks() {
+    const preferred = getPreference(getDefaultNamespace());
+
+    document.querySelectorAll('.langtabs-only').forEach(function(block) {
+        const langs = (block.getAttribute('data-langs') || '').split(' ');
+        const matches = !preferred || langs.includes(preferred);
+        block.hidden = !matches;
+        block.classList.toggle('langtabs-only-selected', Boolean(preferred) && matches);
+    });
+}
+
+function activateTab(button) {
+    const container = button.closest('.langtabs');
+    const lang = button.getAttribute('data-lang');
+    const previousButton = container.querySelector('.langtabs-tab.active');
+
     // Deactivate all tabs in this container
     const tabButtons = container.querySelectorAll('.langtabs-tab');
     const tabContents = container.querySelectorAll('.langtabs-code');
-    
+
     tabButtons.forEach(function(btn) {
         btn.classList.remove('active');
     });
     tabContents.forEach(function(content) {
         content.classList.remove('active');
     });
-    
+
     // Activate selected tab
-    this.classList.add('active');
+    button.classList.add('active');
     const activeContent = container.querySelector(`.langtabs-code[data-lang="${lang}"]`);
     if (activeContent) {
         activeContent.classList.add('active');
     }
-}
\ No newline at end of file
+
+    // Never compare a language with itself
+    if (container.getAttribute('data-view') === 'compare') {
+        const compared = container.querySelector('.langtabs-code.langtabs-compared');
+        if (!compared || compared === activeContent) {
+            setCompared(container, previousButton && previousButton !== button ?
+                previousButton.getAttribute('data-lang') : null);
+        }
+    }
+
+    // Keep the active tab out of the "More" dropdown
+    if (container.classList.contains('langtabs-auto')) {
+        updateOverflow(container);
+    }
+
+    if (previousButton !== button) {
+        container.dispatchEvent(new CustomEvent('langtabs:change', {
+            bubbles: true,
+            detail: {
+                groupId: container.getAttribute('data-group'),
+                namespace: getNamespace(container),
+                previous: previousButton ? previousButton.getAttribute('data-key') : null,
+                current: button.getAttribute('data-key')
+            }
+        }));
+    }
+}
+
+// Show only the variant of inline terms for the preferred language, if there is one
+function updateTerms() {
+    const preferred = getPreference(getDefaultNamespace());
+
+    document.querySelectorAll('.langtabs-term').forEach(function(term) {
+        let selected = false;
+        term.querySelectorAll('.langtabs-term-variant').forEach(function(variant) {
+            const matches = variant.getAttribute('data-lang') === preferred;
+            variant.classList.toggle('active', matches);
+            selected = selected || matches;
+        });
+        term.classList.toggle('langtabs-term-selected', selected);
+    });
+}
+
+// Language dropdown in mdBook's menu bar, driving the default namespace
+function initSwitcher() {
+    const config = getLangTabsConfig();
+    const languages = config.languages;
+    const menuBar = document.querySelector('.menu-bar .left-buttons');
+    if (!config.switcher || languages.length === 0 || !menuBar) {
+        return;
+    }
+
+    const select = document.createElement('select');
+    select.className = 'langtabs-switcher';
+    select.setAttribute('aria-label', 'Programming language');
+
+    const placeholder = document.createElement('option');
+    placeholder.value = '';
+    placeholder.textContent = 'Language';
+    placeholder.disabled = true;
+    select.appendChild(placeholder);
+
+    languages.forEach(function(language) {
+        const option = document.createElement('option');
+        option.value = language.key;
+        option.textContent = language.name;
+        select.appendChild(option);
+    });
+
+    const syncSwitcher = function() {
+        const preferred = getPreference(getDefaultNamespace());
+        select.value = languages.some(function(language) {
+            return language.key === preferred;
+        }) ? preferred : '';
+    };
+    syncSwitcher();
+
+    select.addEventListener('change', function() {
+        selectLanguage(getDefaultNamespace(), select.value, null);
+    });
+    document.addEventListener('langtabs:change', syncSwitcher);
+
+    // Insert next to the theme picker
+    const themeList = menuBar.querySelector('#theme-list');
+    if (themeList) {
+        themeList.after(select);
+    } else {
+        menuBar.appendChild(select);
+    }
+
+    const shortcut = config.switcherShortcut;
+    if (shortcut) {
+        document.addEventListener('keydown', function(event) {
+            if (event.key !== shortcut || event.ctrlKey || event.metaKey || event.altKey) {
+                return;
+            }
+            const target = event.target;
+            if (target.isContentEditable || /^(INPUT|TEXTAREA|SELECT)$/.test(target.tagName)) {
+                return;
+            }
+
+            // Cycle to the next language
+            const index = languages.findIndex(function(language) {
+                return language.key === select.value;
+            });
+            const next = languages[(index + 1) % languages.length];
+            selectLanguage(getDefaultNamespace(), next.key, null);
+            syncSwitcher();
+        });
+    }
+}
+
+function getHiddenLanguages() {
+    try {
+        return JSON.parse(localStorage.getItem(getStorageKey('hidden'))) || [];
+    } catch (e) {
+        return [];
+    }
+}
+
+function setHiddenLanguages(keys) {
+    try {
+        localStorage.setItem(getStorageKey('hidden'), JSON.stringify(keys));
+    } catch (e) {
+        // Storage may be unavailable, e.g. in private browsing
+    }
+}
+
+// Hide the tabs of languages the reader opted out of. A group that would be
+// left without a real tab keeps all of them.
+function applyHiddenLanguages() {
+    const hidden = getHiddenLanguages();
+
+    document.querySelectorAll('.langtabs').forEach(function(container) {
+        if (getNamespace(container) !== getDefaultNamespace()) {
+            return;
+        }
+
+        const tabButtons = Array.from(container.querySelectorAll('.langtabs-tab'));
+        const shown = tabButtons.filter(function(button) {
+            return !hidden.includes(button.getAttribute('data-key'));
+        });
+        const hasContent = shown.some(function(button) {
+            return !button.classList.contains('langtabs-placeholder');
+        });
+
+        tabButtons.forEach(function(button) {
+            button.hidden = hasContent && !shown.includes(button);
+        });
+
+        const active = container.querySelector('.langtabs-tab.active');
+        if (active && active.hidden) {
+            activateTab(shown[0]);
+        }
+    });
+
+    document.querySelectorAll('.langtabs-auto').forEach(updateOverflow);
+}
+
+// Languages readers can choose from: those of the whole book, or of this page
+function getLanguages() {
+    const languages = getLangTabsConfig().languages;
+    if (languages.length > 0) {
+        return languages;
+    }
+
+    const found = [];
+    document.querySelectorAll('.langtabs').forEach(function(container) {
+        if (getNamespace(container) !== getDefaultNamespace()) {
+            return;
+        }
+        container.querySelectorAll('.langtabs-tab:not(.langtabs-placeholder)').forEach(function(button) {
+            const key = button.getAttribute('data-key');
+            if (!found.some(function(language) { return language.key === key; })) {
+                found.push({ key: key, name: getTabName(button) });
+            }
+        });
+    });
+    return found;
+}
+
+// Gear buttons on tab bars, or a button in the menu bar, open a menu to choose
+// which languages are shown
+function initLanguageFilter() {
+    const menuBar = document.querySelector('.menu-bar .left-buttons');
+    if (getLangTabsConfig().languageFilter === 'menu-bar' && menuBar) {
+        const button = document.createElement('button');
+        button.type = 'button';
+        button.className = 'icon-button langtabs-filter-toggle';
+        button.title = 'Choose languages';
+        button.setAttribute('aria-label', 'Choose languages');
+        button.setAttribute('aria-haspopup', 'true');
+        const icon = document.createElement('i');
+        icon.className = 'fa fa-filter';
+        button.appendChild(icon);
+        menuBar.appendChild(button);
+    }
+
+    document.addEventListener('click', function(event) {
+        const toggle = event.target.closest('.langtabs-filter-toggle');
+        const menu = document.querySelector('.langtabs-filter-menu');
+
+        if (toggle) {
+            const isOpen = menu && menu.langtabsToggle === toggle;
+            closeFilterMenu();
+            if (!isOpen) {
+                openFilterMenu(toggle);
+            }
+        } else if (menu && !menu.contains(event.target)) {
+            closeFilterMenu();
+        }
+    });
+
+    document.addEventListener('keydown', function(event) {
+        if (event.key === 'Escape') {
+            closeFilterMenu();
+        }
+    });
+}
+
+function openFilterMenu(toggle) {
+    const hidden = getHiddenLanguages();
+    const menu = document.createElement('div');
+    menu.className = 'langtabs-filter-menu';
+    menu.setAttribute('role', 'dialog');
+    menu.setAttribute('aria-label', 'Choose languages');
+    menu.langtabsToggle = toggle;
+
+    getLanguages().forEach(function(language) {
+        const label = document.createElement('label');
+        const checkbox = document.createElement('input');
+        checkbox.type = 'checkbox';
+        checkbox.value = language.key;
+        checkbox.checked = !hidden.includes(language.key);
+        checkbox.addEventListener('change', function() {
+            const unchecked = menu.querySelectorAll('input:not(:checked)');
+            setHiddenLanguages(Array.from(unchecked).map(function(input) {
+                return input.value;
+            }));
+            applyHiddenLanguages();
+        });
+        label.append(checkbox, ' ' + language.name);
+        menu.appendChild(label);
+    });
+
+    document.body.appendChild(menu);
+    const rect = toggle.getBoundingClientRect();
+    menu.style.top = `${rect.bottom + 4}px`;
+    menu.style.left = `${Math.max(8, Math.min(rect.left, window.innerWidth - menu.offsetWidth - 8))}px`;
+    toggle.setAttribute('aria-expanded', 'true');
+
+    const firstCheckbox = menu.querySelector('input');
+    if (firstCheckbox) {
+        firstCheckbox.focus();
+    }
+}
+
+function closeFilterMenu() {
+    const menu = document.querySelector('.langtabs-filter-menu');
+    if (menu) {
+        menu.langtabsToggle.setAttribute('aria-expanded', 'false');
+        menu.remove();
+    }
+}
+
+// Groups with a `data-view` can show their languages tabbed, stacked, or two side by side
+function initViews() {
+    document.querySelectorAll('.langtabs[data-view]').forEach(function(container) {
+        setView(container, container.getAttribute('data-view'));
+    });
+
+    document.addEventListener('click', function(event) {
+        const button = event.target.closest('.langtabs-view-toggle button');
+        if (button) {
+            setView(button.closest('.langtabs'), button.getAttribute('data-view'));
+        }
+    });
+}
+
+function setView(container, view) {
+    container.setAttribute('data-view', view);
+    container.querySelectorAll('.langtabs-view-toggle button').forEach(function(button) {
+        button.setAttribute('aria-pressed', String(button.getAttribute('data-view') === view));
+    });
+
+    if (view === 'compare') {
+        ensureCompareSelect(container);
+        const compared = container.querySelector('.langtabs-code.langtabs-compared');
+        setCompared(container, compared ? compared.getAttribute('data-lang') : null);
+    }
+}
+
+// The dropdown that picks the language shown next to the selected tab
+function ensureCompareSelect(container) {
+    const header = container.querySelector('.langtabs-header');
+    if (header.querySelector('.langtabs-compare-select')) {
+        return;
+    }
+
+    const select = document.createElement('select');
+    select.className = 'langtabs-compare-select';
+    select.setAttribute('aria-label', 'Compare with');
+    container.querySelectorAll('.langtabs-tab:not(.langtabs-placeholder)').forEach(function(tab) {
+        const option = document.createElement('option');
+        option.value = tab.getAttribute('data-lang');
+        option.textContent = getTabName(tab);
+        select.appendChild(option);
+    });
+    select.addEventListener('change', function() {
+        const active = container.querySelector('.langtabs-tab.active');
+        // Picking the language on the left swaps the two
+        if (active && active.getAttribute('data-lang') === select.value) {
+            const compared = container.querySelector('.langtabs-code.langtabs-compared');
+            const tab = compared && container.querySelector(
+                `.langtabs-tab[data-lang="${compared.getAttribute('data-lang')}"]`);
+            if (tab) {
+                tab.click();
+            }
+            return;
+        }
+        setCompared(container, select.value);
+    });
+
+    header.insertBefore(select, header.querySelector('.langtabs-view-toggle'));
+}
+
+// Show the panel for `lang` next to the active one, or the first other panel if null
+function setCompared(container, lang) {
+    const active = container.querySelector('.langtabs-code.active');
+    const panels = Array.from(container.querySelectorAll('.langtabs-code'));
+    const panel = panels.find(function(candidate) {
+        return candidate !== active && (lang ?
+            candidate.getAttribute('data-lang') === lang :
+            !container.querySelector(`.langtabs-tab.langtabs-placeholder[data-lang="${candidate.getAttribute('data-lang')}"]`));
+    });
+
+    panels.forEach(function(candidate) {
+        candidate.classList.toggle('langtabs-compared', candidate === panel);
+    });
+
+    const select = container.querySelector('.langtabs-compare-select');
+    if (select && panel) {
+        select.value = panel.getAttribute('data-lang');
+    }
+}
+
+// `auto` groups collapse the tabs that don't fit into a "More" dropdown
+function initMoreMenus() {
+    let resizeTimer = null;
+    window.addEventListener('resize', function() {
+        clearTimeout(resizeTimer);
+        resizeTimer = setTimeout(function() {
+            document.querySelectorAll('.langtabs-auto').forEach(updateOverflow);
+        }, 100);
+    });
+
+    document.addEventListener('click', function(event) {
+        const menu = document.querySelector('.langtabs-more-menu');
+        if (menu && !menu.contains(event.target) && !event.target.closest('.langtabs-more')) {
+            closeMoreMenu();
+        }
+    });
+
+    document.addEventListener('keydown', function(event) {
+        if (event.key === 'Escape') {
+            closeMoreMenu();
+        }
+    });
+}
+
+// Move tabs from the end of the bar into the dropdown until the rest fit,
+// never moving the active one
+function updateOverflow(container) {
+    const header = container.querySelector('.langtabs-header');
+    let more = header.querySelector('.langtabs-more');
+    if (!more) {
+        more = document.createElement('button');
+        more.type = 'button';
+        more.className = 'langtabs-more';
+        more.textContent = 'More ▾';
+        more.setAttribute('aria-haspopup', 'true');
+        more.setAttribute('aria-expanded', 'false');
+        more.addEventListener('click', toggleMoreMenu);
+        header.insertBefore(more, header.querySelector('.langtabs-filter-toggle'));
+    }
+
+    const tabs = Array.from(header.querySelectorAll('.langtabs-tab')).filter(function(tab) {
+        return !tab.hidden;
+    });
+    tabs.forEach(function(tab) {
+        tab.classList.remove('langtabs-overflow');
+    });
+    more.hidden = true;
+    more.langtabsOverflow = [];
+
+    // Groups that aren't displayed, e.g. in a closed <details>, can't be measured
+    if (header.clientWidth === 0 || header.scrollWidth <= header.clientWidth) {
+        return;
+    }
+
+    more.hidden = false;
+    const active = header.querySelector('.langtabs-tab.active');
+    for (let i = tabs.length - 1; i >= 0 && header.scrollWidth > header.clientWidth; i--) {
+        if (tabs[i] !== active) {
+            tabs[i].classList.add('langtabs-overflow');
+            more.langtabsOverflow.unshift(tabs[i]);
+        }
+    }
+}
+
+function toggleMoreMenu() {
+    const more = this;
+    const isOpen = more.getAttribute('aria-expanded') === 'true';
+    closeMoreMenu();
+    if (isOpen) {
+        return;
+    }
+
+    const menu = document.createElement('div');
+    menu.className = 'langtabs-more-menu';
+    menu.setAttribute('role', 'menu');
+    menu.langtabsToggle = more;
+
+    more.langtabsOverflow.forEach(function(tab) {
+        const item = document.createElement('button');
+        item.type = 'button';
+        item.className = 'langtabs-more-item';
+        item.setAttribute('role', 'menuitem');
+        item.textContent = getTabName(tab);
+        item.addEventListener('click', function() {
+            closeMoreMenu();
+            tab.click();
+        });
+        menu.appendChild(item);
+    });
+
+    document.body.appendChild(menu);
+    const rect = more.getBoundingClientRect();
+    menu.style.top = `${rect.bottom + 4}px`;
+    menu.style.left = `${Math.max(8, Math.min(rect.left, window.innerWidth - menu.offsetWidth - 8))}px`;
+    more.setAttribute('aria-expanded', 'true');
+
+    const firstItem = menu.querySelector('button');
+    if (firstItem) {
+        firstItem.focus();
+    }
+}
+
+function closeMoreMenu() {
+    const menu = document.querySelector('.langtabs-more-menu');
+    if (menu) {
+        menu.langtabsToggle.setAttribute('aria-expanded', 'false');
+        menu.remove();
+    }
+}
+
+// Public API for other scripts on the page
+window.langtabs = {
+    // Select `lang` in every group of the namespace and remember it
+    select: function(lang, options) {
+        const namespace = (options && options.namespace) || getDefaultNamespace();
+        selectLanguage(namespace, normalizeKey(lang), null);
+    },
+
+    getPreference: function(namespace) {
+        return getPreference(namespace || getDefaultNamespace());
+    },
+
+    // Call `callback` with the event detail whenever a group changes tab.
+    // Returns a function that removes the subscription.
+    subscribe: function(callback) {
+        const listener = function(event) {
+            callback(event.detail);
+        };
+        document.addEventListener('langtabs:change', listener);
+        return function() {
+            document.removeEventListener('langtabs:change', listener);
+        };
+    }
+};
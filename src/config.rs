@@ -0,0 +1,748 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use mdbook::errors::Error;
+use mdbook::preprocess::PreprocessorContext;
+use serde::{Deserialize, Serialize};
+
+use crate::languages::{Language, LanguageRegistry};
+use crate::preprocessor::slugify;
+
+/// Namespace used by tab groups that don't specify one.
+pub const DEFAULT_NAMESPACE: &str = "lang";
+
+/// Keys of `[preprocessor.langtabs.style]`, emitted as `--langtabs-<key>` custom properties.
+const STYLE_VARIABLES: &[&str] = &[
+    "font-family",
+    "tab-padding",
+    "tab-radius",
+    "tab-gap",
+    "tab-font-size",
+    "tab-color",
+    "active-color",
+    "active-background",
+    "border-color",
+    "content-radius",
+    "icon-size",
+];
+
+/// mdBook's built-in themes, which can override the style variables.
+const THEMES: &[&str] = &["light", "rust", "coal", "navy", "ayu"];
+
+/// Operating systems `langtabs.js` can detect, the keys of `os-labels`.
+const OPERATING_SYSTEMS: &[&str] = &["linux", "macos", "windows"];
+
+/// Keys of the preprocessor table that are read by mdBook itself.
+const MDBOOK_KEYS: &[&str] = &["command", "renderers", "before", "after", "optional"];
+
+/// Configuration read from the `[preprocessor.langtabs]` table of `book.toml`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LangTabsConfig {
+    /// Name of the comment that starts a group, e.g. `<!-- langtabs-start -->`.
+    pub start_marker: String,
+    /// Name of the comment that ends a group.
+    pub end_marker: String,
+    /// Name of the comment that sets the label of the next tab.
+    pub tab_marker: String,
+    /// Tab selected before the reader picks one. Defaults to the first tab.
+    pub default_tab: Option<String>,
+    /// Order of the tabs in every group. Unlisted tabs follow in source order.
+    pub order: Vec<String>,
+    /// Fail the build on malformed groups instead of rendering them as well as possible.
+    pub strict: bool,
+    /// Where language icons come from.
+    pub icons: IconSource,
+    /// Directory in the book's `src` with SVG files that replace the embedded badges of
+    /// `icons = "bundled"`.
+    pub icon_dir: String,
+    /// Stylesheet loaded by `icons = "cdn"`. `{version}` is replaced with `devicon-version`.
+    pub cdn_url: String,
+    pub devicon_version: String,
+    /// Subresource Integrity hash of the CDN stylesheet, e.g. `sha384-...`.
+    pub cdn_integrity: Option<String>,
+    /// Nonce set on the elements `langtabs.js` adds, for a Content-Security-Policy
+    /// that uses `'nonce-...'` sources.
+    pub nonce: Option<String>,
+    /// What tab buttons show: `icon+text`, `text` or `icon`.
+    pub labels: LabelMode,
+    /// How the tab bar is laid out.
+    pub layout: Layout,
+    /// How groups show their languages before the reader changes it.
+    pub view: View,
+    /// Add buttons to switch between the tabbed, stacked and compare views.
+    pub view_toggle: bool,
+    /// Which panels are printed: the active one, all, or those of the listed languages.
+    pub print: Print,
+    /// How groups are rendered for each renderer, e.g. `epub = "headings"`. Renderers
+    /// that aren't listed get tabs for `html`, plain code blocks for `test` (as run by
+    /// `mdbook test`) and headings otherwise.
+    pub output: BTreeMap<String, OutputStyle>,
+    /// Languages defined or adjusted in `[preprocessor.langtabs.languages.<id>]`.
+    pub languages: BTreeMap<String, CustomLanguage>,
+    /// Prefix of the keys under which preferences are stored in the browser. Books
+    /// on the same domain can use different prefixes to keep their choices apart.
+    pub storage_prefix: String,
+    /// Query parameter used to select tabs for each namespace, e.g. `lang = "lang"`.
+    /// Namespaces that aren't listed use their own name.
+    pub query_params: BTreeMap<String, String>,
+    /// Keep the page URL in sync with the selected tabs.
+    pub update_url: bool,
+    /// Tabs to show when a group lacks the preferred one, e.g. `typescript = ["javascript"]`.
+    pub fallbacks: BTreeMap<String, Vec<String>>,
+    /// Note shown when a fallback is used. `{preferred}` and `{fallback}` are replaced
+    /// with the tab names.
+    pub fallback_note: String,
+    /// Languages every group in the default namespace should offer. Missing ones get a
+    /// placeholder tab.
+    pub required: Vec<String>,
+    /// Text shown in placeholder tabs. `{language}` is replaced with the language name.
+    pub placeholder_text: String,
+    /// Optional link shown in placeholder tabs, e.g. to a contribution guide.
+    pub placeholder_link: Option<String>,
+    pub placeholder_link_text: String,
+    /// Size groups to their tallest panel so switching tabs doesn't move the page.
+    pub equal_height: bool,
+    /// Keep the tab bar visible while scrolling through long samples.
+    pub sticky_tabs: bool,
+    /// Add a language dropdown to mdBook's menu bar.
+    pub switcher: bool,
+    /// Key that cycles through the languages of the switcher. Empty to disable.
+    pub switcher_shortcut: String,
+    /// Namespaces whose groups default to the reader's operating system.
+    pub os_namespaces: Vec<String>,
+    /// Tab labels that count as each operating system (`linux`, `macos` or `windows`),
+    /// merged over the defaults.
+    pub os_labels: BTreeMap<String, Vec<String>>,
+    /// Where readers can choose which languages to show.
+    pub language_filter: LanguageFilter,
+    /// Index each panel with its language name, so search results show e.g. "(Python)".
+    pub search_tags: bool,
+    /// Values for the `--langtabs-*` custom properties, with per-theme tables such as
+    /// `[preprocessor.langtabs.style.coal]`.
+    pub style: BTreeMap<String, StyleValue>,
+    /// Built-in languages merged with `languages`.
+    #[serde(skip)]
+    pub registry: LanguageRegistry,
+    /// SVG markup of the files in `icon_dir` by name, read once for `icons = "bundled"`.
+    #[serde(skip)]
+    pub icon_files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LanguageFilter {
+    None,
+    /// A gear button on every tab bar
+    Gear,
+    /// A button in mdBook's menu bar
+    MenuBar,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(
+    untagged,
+    expecting = "expected a CSS value, or a table of values for a theme"
+)]
+pub enum StyleValue {
+    Value(String),
+    /// Overrides for one of mdBook's themes
+    Theme(BTreeMap<String, String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LabelMode {
+    #[serde(rename = "icon+text")]
+    IconText,
+    Text,
+    /// Icons only, with the name as tooltip and accessible label
+    Icon,
+}
+
+impl LabelMode {
+    /// Parse the `labels` option of a start marker.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "icon+text" => Some(Self::IconText),
+            "text" => Some(Self::Text),
+            "icon" => Some(Self::Icon),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    Tabs,
+    Pills,
+    /// Tab list on the left of the code
+    Vertical,
+    /// Tabs, with the ones that don't fit collapsed into a "More" dropdown
+    Auto,
+}
+
+impl Layout {
+    /// Parse the `layout` option of a start marker.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tabs" => Some(Self::Tabs),
+            "pills" => Some(Self::Pills),
+            "vertical" => Some(Self::Vertical),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    /// Class added to the group, if the layout differs from the default tabs.
+    pub fn class(self) -> Option<&'static str> {
+        match self {
+            Self::Tabs => None,
+            Self::Pills => Some("langtabs-pills"),
+            Self::Vertical => Some("langtabs-vertical"),
+            Self::Auto => Some("langtabs-auto"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum View {
+    /// One language at a time
+    Tabbed,
+    /// All languages below each other, with headings
+    Stacked,
+    /// Two languages side by side
+    Compare,
+}
+
+impl View {
+    /// Parse the `view` option of a start marker.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tabbed" => Some(Self::Tabbed),
+            "stacked" => Some(Self::Stacked),
+            "compare" => Some(Self::Compare),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tabbed => "tabbed",
+            Self::Stacked => "stacked",
+            Self::Compare => "compare",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(
+    untagged,
+    expecting = r#"expected "active", "all" or a list of languages"#
+)]
+pub enum Print {
+    Mode(PrintMode),
+    /// The panels of these languages, stacked
+    Languages(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrintMode {
+    /// Only the selected panel, as on screen
+    Active,
+    /// All panels, stacked
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputStyle {
+    /// Interactive tabs, for the HTML renderer
+    Tabs,
+    /// The code blocks one after another, each after a bold language heading
+    Headings,
+    /// The code blocks one after another, without headings
+    Plain,
+}
+
+/// A language added to, or overriding one of, the built-in languages.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CustomLanguage {
+    /// Other names the language can be referred to by, e.g. in code fences
+    pub aliases: Vec<String>,
+    pub display_name: Option<String>,
+    /// Icon class, e.g. `devicon-zig-original`, or path of an SVG/PNG file in the book
+    pub icon: Option<String>,
+    /// Language whose highlighting code blocks use, by id or alias
+    pub highlight_as: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconSource {
+    /// Devicon, loaded from jsDelivr
+    Cdn,
+    /// SVG files from `icon-dir`, inlined into the pages, with generated
+    /// monograms for languages that have none
+    Bundled,
+    /// No icons, only language names
+    None,
+}
+
+impl Default for LangTabsConfig {
+    fn default() -> Self {
+        LangTabsConfig {
+            start_marker: "langtabs-start".to_string(),
+            end_marker: "langtabs-end".to_string(),
+            tab_marker: "langtabs-tab".to_string(),
+            default_tab: None,
+            order: Vec::new(),
+            strict: false,
+            icons: IconSource::Cdn,
+            icon_dir: "langtabs-icons".to_string(),
+            cdn_url: "https://cdn.jsdelivr.net/gh/devicons/devicon@v{version}/devicon.min.css"
+                .to_string(),
+            devicon_version: "2.16.0".to_string(),
+            cdn_integrity: None,
+            nonce: None,
+            labels: LabelMode::IconText,
+            layout: Layout::Tabs,
+            view: View::Tabbed,
+            view_toggle: false,
+            print: Print::Mode(PrintMode::Active),
+            output: BTreeMap::new(),
+            languages: BTreeMap::new(),
+            storage_prefix: "langtabs-".to_string(),
+            query_params: BTreeMap::new(),
+            update_url: true,
+            fallbacks: BTreeMap::new(),
+            fallback_note: "Not available in {preferred}, showing {fallback}".to_string(),
+            required: Vec::new(),
+            placeholder_text: "The {language} example is coming soon.".to_string(),
+            placeholder_link: None,
+            placeholder_link_text: "Contribute an example".to_string(),
+            equal_height: false,
+            sticky_tabs: false,
+            switcher: false,
+            switcher_shortcut: "l".to_string(),
+            os_namespaces: vec!["os".to_string()],
+            os_labels: BTreeMap::from([
+                (
+                    "linux".to_string(),
+                    ["Linux", "Ubuntu", "Debian", "Fedora", "Arch"]
+                        .map(String::from)
+                        .to_vec(),
+                ),
+                (
+                    "macos".to_string(),
+                    ["macOS", "Mac", "OS X", "OSX"].map(String::from).to_vec(),
+                ),
+                (
+                    "windows".to_string(),
+                    ["Windows", "Win", "PowerShell"].map(String::from).to_vec(),
+                ),
+            ]),
+            language_filter: LanguageFilter::None,
+            search_tags: false,
+            style: BTreeMap::new(),
+            registry: LanguageRegistry::builtin(),
+            icon_files: BTreeMap::new(),
+        }
+    }
+}
+
+impl LangTabsConfig {
+    pub fn from_context(ctx: &PreprocessorContext) -> Result<Self, Error> {
+        let Some(table) = ctx.config.get_preprocessor("langtabs") else {
+            return Ok(Self::default());
+        };
+
+        let mut options = serde_json::to_value(table)?
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+        for key in MDBOOK_KEYS {
+            options.remove(*key);
+        }
+
+        let mut config: Self = serde_json::from_value(serde_json::Value::Object(options.clone()))
+            .map_err(|error| {
+            // serde doesn't name the offending key for type errors, so look for it
+            let key = options.iter().find(|(key, value)| {
+                let single = serde_json::Map::from_iter([((*key).clone(), (*value).clone())]);
+                serde_json::from_value::<Self>(serde_json::Value::Object(single)).is_err()
+            });
+
+            match key {
+                Some((key, _)) => Error::msg(format!(
+                    "Invalid `preprocessor.langtabs.{}` in book.toml: {}",
+                    key, error
+                )),
+                None => Error::msg(format!(
+                    "Invalid `preprocessor.langtabs` configuration in book.toml: {}",
+                    error
+                )),
+            }
+        })?;
+
+        config.check_style()?;
+        config.merge_os_labels()?;
+        config.registry = LanguageRegistry::with_custom(&config.languages);
+        config.check_languages()?;
+        if config.icons == IconSource::Bundled {
+            let dir = ctx.root.join(&ctx.config.book.src).join(&config.icon_dir);
+            config.icon_files = read_icon_files(&dir)?;
+        }
+        Ok(config)
+    }
+
+    // Reject unknown variables and themes, which serde can't check in a map
+    fn check_style(&self) -> Result<(), Error> {
+        let check_variable = |key: &str, path: &str| {
+            if STYLE_VARIABLES.contains(&key) {
+                Ok(())
+            } else {
+                Err(Error::msg(format!(
+                    "Unknown `preprocessor.langtabs.style.{}{}` in book.toml, expected one of: {}",
+                    path,
+                    key,
+                    STYLE_VARIABLES.join(", ")
+                )))
+            }
+        };
+
+        for (key, value) in &self.style {
+            match value {
+                StyleValue::Value(_) => check_variable(key, "")?,
+                StyleValue::Theme(variables) => {
+                    if !THEMES.contains(&key.as_str()) {
+                        return Err(Error::msg(format!(
+                            "Unknown theme `preprocessor.langtabs.style.{}` in book.toml, expected one of: {}",
+                            key,
+                            THEMES.join(", ")
+                        )));
+                    }
+                    for name in variables.keys() {
+                        check_variable(name, &format!("{}.", key))?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Operating systems that aren't set keep their default labels
+    fn merge_os_labels(&mut self) -> Result<(), Error> {
+        for os in self.os_labels.keys() {
+            if !OPERATING_SYSTEMS.contains(&os.as_str()) {
+                return Err(Error::msg(format!(
+                    "Unknown operating system `preprocessor.langtabs.os-labels.{}` in book.toml, expected one of: {}",
+                    os,
+                    OPERATING_SYSTEMS.join(", ")
+                )));
+            }
+        }
+
+        let mut os_labels = Self::default().os_labels;
+        os_labels.append(&mut self.os_labels);
+        self.os_labels = os_labels;
+        Ok(())
+    }
+
+    // `highlight-as` must name a language, since highlight.js only knows its own names
+    fn check_languages(&self) -> Result<(), Error> {
+        for (id, definition) in &self.languages {
+            if let Some(highlight_as) = &definition.highlight_as
+                && self.registry.get(highlight_as).is_none()
+            {
+                return Err(Error::msg(format!(
+                    "Unknown language `{}` in `preprocessor.langtabs.languages.{}.highlight-as` in book.toml",
+                    highlight_as, id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// How groups are rendered for `renderer`.
+    pub fn output_style(&self, renderer: &str) -> OutputStyle {
+        match self.output.get(renderer) {
+            Some(style) => *style,
+            None if renderer == "html" => OutputStyle::Tabs,
+            // `mdbook test` should see every code block as written
+            None if renderer == "test" => OutputStyle::Plain,
+            None => OutputStyle::Headings,
+        }
+    }
+
+    /// Look up a language by name or alias, including those defined in `book.toml`.
+    pub fn language(&self, name: &str) -> Language {
+        self.registry.resolve(name)
+    }
+
+    /// Normalise a language name or alias to its tab key, e.g. "ts" -> "typescript".
+    /// Other names are taken as tab labels, e.g. "macOS" -> "macos".
+    pub fn tab_key(&self, name: &str) -> String {
+        let language = self.language(name);
+        if language.known {
+            language.id
+        } else {
+            slugify(name)
+        }
+    }
+
+    /// The comment that starts a group, without the closing `-->` so it can carry options.
+    pub fn start_comment(&self) -> String {
+        format!("<!-- {}", self.start_marker)
+    }
+
+    pub fn end_comment(&self) -> String {
+        format!("<!-- {} -->", self.end_marker)
+    }
+
+    /// Settings needed by `langtabs.js`, embedded into every HTML page.
+    /// Keep the keys in sync with `LANGTABS_DEFAULTS` in `langtabs.js`.
+    /// `languages` are the languages used across the book.
+    pub fn page_config(&self, languages: Vec<PageLanguage>) -> PageConfig {
+        let fallbacks: BTreeMap<String, Vec<String>> = self
+            .fallbacks
+            .iter()
+            .map(|(from, to)| {
+                (
+                    self.tab_key(from),
+                    to.iter().map(|key| self.tab_key(key)).collect(),
+                )
+            })
+            .collect();
+
+        // Names for the languages in fallback chains, which may not have a tab on the page
+        let names = fallbacks
+            .iter()
+            .flat_map(|(from, to)| std::iter::once(from).chain(to))
+            .map(|key| (key.clone(), self.language(key).display_name))
+            .collect();
+
+        let mut style = BTreeMap::new();
+        let mut theme_styles = BTreeMap::new();
+        for (key, value) in &self.style {
+            match value {
+                StyleValue::Value(value) => {
+                    style.insert(key.clone(), value.clone());
+                }
+                StyleValue::Theme(variables) => {
+                    theme_styles.insert(key.clone(), variables.clone());
+                }
+            }
+        }
+
+        PageConfig {
+            style,
+            theme_styles,
+            default_namespace: DEFAULT_NAMESPACE,
+            storage_prefix: self.storage_prefix.clone(),
+            default_tab: self.default_tab.as_ref().map(|name| self.tab_key(name)),
+            aliases: self.registry.aliases(),
+            query_params: self.query_params.clone(),
+            update_url: self.update_url,
+            fallbacks,
+            fallback_note: self.fallback_note.clone(),
+            names,
+            switcher: self.switcher,
+            switcher_shortcut: self.switcher_shortcut.clone(),
+            languages,
+            language_filter: self.language_filter,
+            icons: self.icons,
+            cdn_url: self.cdn_url.replace("{version}", &self.devicon_version),
+            cdn_integrity: self.cdn_integrity.clone(),
+            nonce: self.nonce.clone(),
+        }
+    }
+}
+
+// The SVG files in `dir` by file stem. The directory is optional, since the
+// built-in languages have embedded icons.
+fn read_icon_files(dir: &Path) -> Result<BTreeMap<String, String>, Error> {
+    let mut files = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(files);
+    };
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "svg") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let svg = fs::read_to_string(&path)
+            .map_err(|error| Error::msg(format!("Failed to read {}: {}", path.display(), error)))?;
+
+        // Drop the XML prolog and keep the markup on one line, so it stays inside the
+        // surrounding HTML block
+        if let Some(start) = svg.find("<svg") {
+            let svg = svg[start..]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            files.insert(name.to_string(), svg);
+        }
+    }
+
+    Ok(files)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageConfig {
+    style: BTreeMap<String, String>,
+    theme_styles: BTreeMap<String, BTreeMap<String, String>>,
+    default_namespace: &'static str,
+    storage_prefix: String,
+    default_tab: Option<String>,
+    aliases: BTreeMap<String, String>,
+    query_params: BTreeMap<String, String>,
+    update_url: bool,
+    fallbacks: BTreeMap<String, Vec<String>>,
+    fallback_note: String,
+    names: BTreeMap<String, String>,
+    switcher: bool,
+    switcher_shortcut: String,
+    languages: Vec<PageLanguage>,
+    language_filter: LanguageFilter,
+    icons: IconSource,
+    cdn_url: String,
+    cdn_integrity: Option<String>,
+    nonce: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageLanguage {
+    pub key: String,
+    pub name: String,
+}
+
+impl PageConfig {
+    /// Render as a JSON script element that `langtabs.js` reads on load.
+    pub fn to_html(&self) -> String {
+        let json = serde_json::to_string(self)
+            .expect("Failed to serialize page config")
+            .replace("</", r"<\/");
+
+        format!(
+            "\n\n<script type=\"application/json\" class=\"langtabs-config\">{}</script>\n",
+            json
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(options: serde_json::Value) -> Result<LangTabsConfig, Error> {
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": "/nonexistent",
+            "config": {
+                "book": { "title": "Test", "src": "src", "authors": [], "language": "en", "multilingual": false },
+                "preprocessor": { "langtabs": options },
+            },
+            "renderer": "html",
+            "mdbook_version": "0.4.48",
+        }))
+        .unwrap();
+        LangTabsConfig::from_context(&ctx)
+    }
+
+    #[test]
+    fn mdbook_keys_are_accepted() {
+        let config = load(serde_json::json!({
+            "command": "mdbook-langtabs",
+            "renderers": ["html"],
+            "default-tab": "py",
+        }))
+        .unwrap();
+        assert_eq!(config.default_tab.as_deref(), Some("py"));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let error = load(serde_json::json!({ "defualt-tab": "rust" })).unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.contains("`preprocessor.langtabs.defualt-tab`"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("unknown field `defualt-tab`"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn wrong_types_name_the_key() {
+        let error = load(serde_json::json!({
+            "strict": true,
+            "order": "rust",
+        }))
+        .unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.contains("`preprocessor.langtabs.order`"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn unknown_style_variables_are_rejected() {
+        let error = load(serde_json::json!({ "style": { "tab-colour": "red" } })).unwrap_err();
+        assert!(error.to_string().contains("style.tab-colour"));
+    }
+
+    #[test]
+    fn highlight_as_must_name_a_language() {
+        let config = load(serde_json::json!({
+            "languages": { "acme": { "highlight-as": "terraform" } },
+        }))
+        .unwrap();
+        assert_eq!(config.language("acme").highlight_as.as_deref(), Some("hcl"));
+
+        let error = load(serde_json::json!({
+            "languages": { "acme": { "highlight-as": "nope" } },
+        }))
+        .unwrap_err();
+        assert!(error.to_string().contains("Unknown language `nope`"));
+    }
+
+    #[test]
+    fn tab_keys() {
+        let config = LangTabsConfig::default();
+        assert_eq!(config.tab_key("ts"), "typescript");
+        assert_eq!(config.tab_key("pyi"), "python");
+        assert_eq!(config.tab_key("macOS 14"), "macos-14");
+    }
+
+    #[test]
+    fn os_labels_merge_over_the_defaults() {
+        let config = load(serde_json::json!({
+            "os-labels": { "linux": ["Linux", "Mint"] },
+        }))
+        .unwrap();
+        assert_eq!(config.os_labels["linux"], ["Linux", "Mint"]);
+        assert!(config.os_labels["macos"].contains(&"macOS".to_string()));
+        assert!(config.os_labels["windows"].contains(&"Windows".to_string()));
+
+        let error = load(serde_json::json!({ "os-labels": { "mac": ["Mac"] } })).unwrap_err();
+        assert!(error.to_string().contains("os-labels.mac"));
+    }
+}
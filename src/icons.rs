@@ -0,0 +1,79 @@
+// SVG badges embedded for `icons = "bundled"`, by built-in language id: the
+// language's colour with a short abbreviation. They are not Devicon's artwork,
+// which books can put in `icon-dir` instead.
+#[rustfmt::skip]
+const BUNDLED_ICONS: &[(&str, &str)] = &[
+    ("apache", include_str!("../assets/icons/apache.svg")),
+    ("bash", include_str!("../assets/icons/bash.svg")),
+    ("batch", include_str!("../assets/icons/batch.svg")),
+    ("c", include_str!("../assets/icons/c.svg")),
+    ("clojure", include_str!("../assets/icons/clojure.svg")),
+    ("cmake", include_str!("../assets/icons/cmake.svg")),
+    ("coffeescript", include_str!("../assets/icons/coffeescript.svg")),
+    ("cpp", include_str!("../assets/icons/cpp.svg")),
+    ("crystal", include_str!("../assets/icons/crystal.svg")),
+    ("csharp", include_str!("../assets/icons/csharp.svg")),
+    ("css", include_str!("../assets/icons/css.svg")),
+    ("dart", include_str!("../assets/icons/dart.svg")),
+    ("diff", include_str!("../assets/icons/diff.svg")),
+    ("dockerfile", include_str!("../assets/icons/dockerfile.svg")),
+    ("elixir", include_str!("../assets/icons/elixir.svg")),
+    ("elm", include_str!("../assets/icons/elm.svg")),
+    ("erlang", include_str!("../assets/icons/erlang.svg")),
+    ("fortran", include_str!("../assets/icons/fortran.svg")),
+    ("fsharp", include_str!("../assets/icons/fsharp.svg")),
+    ("glsl", include_str!("../assets/icons/glsl.svg")),
+    ("go", include_str!("../assets/icons/go.svg")),
+    ("graphql", include_str!("../assets/icons/graphql.svg")),
+    ("groovy", include_str!("../assets/icons/groovy.svg")),
+    ("handlebars", include_str!("../assets/icons/handlebars.svg")),
+    ("haskell", include_str!("../assets/icons/haskell.svg")),
+    ("hcl", include_str!("../assets/icons/hcl.svg")),
+    ("html", include_str!("../assets/icons/html.svg")),
+    ("java", include_str!("../assets/icons/java.svg")),
+    ("javascript", include_str!("../assets/icons/javascript.svg")),
+    ("json", include_str!("../assets/icons/json.svg")),
+    ("julia", include_str!("../assets/icons/julia.svg")),
+    ("kotlin", include_str!("../assets/icons/kotlin.svg")),
+    ("latex", include_str!("../assets/icons/latex.svg")),
+    ("less", include_str!("../assets/icons/less.svg")),
+    ("lua", include_str!("../assets/icons/lua.svg")),
+    ("makefile", include_str!("../assets/icons/makefile.svg")),
+    ("markdown", include_str!("../assets/icons/markdown.svg")),
+    ("matlab", include_str!("../assets/icons/matlab.svg")),
+    ("nginx", include_str!("../assets/icons/nginx.svg")),
+    ("nim", include_str!("../assets/icons/nim.svg")),
+    ("nix", include_str!("../assets/icons/nix.svg")),
+    ("objectivec", include_str!("../assets/icons/objectivec.svg")),
+    ("ocaml", include_str!("../assets/icons/ocaml.svg")),
+    ("pascal", include_str!("../assets/icons/pascal.svg")),
+    ("perl", include_str!("../assets/icons/perl.svg")),
+    ("php", include_str!("../assets/icons/php.svg")),
+    ("powershell", include_str!("../assets/icons/powershell.svg")),
+    ("prolog", include_str!("../assets/icons/prolog.svg")),
+    ("python", include_str!("../assets/icons/python.svg")),
+    ("r", include_str!("../assets/icons/r.svg")),
+    ("ruby", include_str!("../assets/icons/ruby.svg")),
+    ("rust", include_str!("../assets/icons/rust.svg")),
+    ("scala", include_str!("../assets/icons/scala.svg")),
+    ("scss", include_str!("../assets/icons/scss.svg")),
+    ("shell", include_str!("../assets/icons/shell.svg")),
+    ("solidity", include_str!("../assets/icons/solidity.svg")),
+    ("svelte", include_str!("../assets/icons/svelte.svg")),
+    ("swift", include_str!("../assets/icons/swift.svg")),
+    ("typescript", include_str!("../assets/icons/typescript.svg")),
+    ("vbnet", include_str!("../assets/icons/vbnet.svg")),
+    ("vim", include_str!("../assets/icons/vim.svg")),
+    ("vue", include_str!("../assets/icons/vue.svg")),
+    ("wasm", include_str!("../assets/icons/wasm.svg")),
+    ("xml", include_str!("../assets/icons/xml.svg")),
+    ("zig", include_str!("../assets/icons/zig.svg")),
+];
+
+/// The embedded badge for a built-in language id such as `rust`.
+pub fn bundled(id: &str) -> Option<&'static str> {
+    BUNDLED_ICONS
+        .iter()
+        .find(|(language, _)| *language == id)
+        .map(|(_, svg)| svg.trim_end())
+}
@@ -1,253 +1,371 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::CustomLanguage;
+
+/// Icon used for languages without one of their own.
+const GENERIC_ICON: &str = "devicon-devicon-plain";
+
+/// A row of the built-in language table.
+struct Builtin {
+    id: &'static str,
+    aliases: &'static [&'static str],
+    extensions: &'static [&'static str],
+    /// highlight.js language name
+    highlight: &'static str,
+    display_name: &'static str,
+    icon_class: &'static str,
+}
+
+#[rustfmt::skip]
+const BUILTIN_LANGUAGES: &[Builtin] = &[
+    Builtin { id: "ada", aliases: &[], extensions: &["adb", "ads"], highlight: "ada", display_name: "Ada", icon_class: GENERIC_ICON },
+    Builtin { id: "apache", aliases: &[], extensions: &[], highlight: "apache", display_name: "Apache", icon_class: "devicon-apache-plain" },
+    Builtin { id: "armasm", aliases: &[], extensions: &[], highlight: "armasm", display_name: "ARM Assembly", icon_class: GENERIC_ICON },
+    Builtin { id: "bash", aliases: &[], extensions: &["bash"], highlight: "bash", display_name: "Bash", icon_class: "devicon-bash-plain" },
+    Builtin { id: "batch", aliases: &["bat", "cmd"], extensions: &["bat", "cmd"], highlight: "dos", display_name: "Batch", icon_class: "devicon-windows8-original" },
+    Builtin { id: "c", aliases: &[], extensions: &["c", "h"], highlight: "c", display_name: "C", icon_class: "devicon-c-plain" },
+    Builtin { id: "clojure", aliases: &["clj", "cljs", "edn"], extensions: &["clj", "cljs", "cljc", "edn"], highlight: "clojure", display_name: "Clojure", icon_class: "devicon-clojure-plain" },
+    Builtin { id: "cmake", aliases: &[], extensions: &["cmake"], highlight: "cmake", display_name: "CMake", icon_class: "devicon-cmake-plain" },
+    Builtin { id: "cobol", aliases: &[], extensions: &["cob", "cbl"], highlight: "cobol", display_name: "COBOL", icon_class: GENERIC_ICON },
+    Builtin { id: "coffeescript", aliases: &[], extensions: &["coffee"], highlight: "coffeescript", display_name: "CoffeeScript", icon_class: "devicon-coffeescript-plain" },
+    Builtin { id: "cpp", aliases: &["c++"], extensions: &["cpp", "cc", "cxx", "hpp"], highlight: "cpp", display_name: "C++", icon_class: "devicon-cplusplus-plain" },
+    Builtin { id: "crystal", aliases: &["cr"], extensions: &["cr"], highlight: "crystal", display_name: "Crystal", icon_class: "devicon-crystal-original" },
+    Builtin { id: "csharp", aliases: &["cs", "c#"], extensions: &["cs"], highlight: "csharp", display_name: "C#", icon_class: "devicon-csharp-plain" },
+    Builtin { id: "css", aliases: &[], extensions: &["css"], highlight: "css", display_name: "CSS", icon_class: "devicon-css3-plain" },
+    Builtin { id: "csv", aliases: &[], extensions: &["csv"], highlight: "plaintext", display_name: "CSV", icon_class: GENERIC_ICON },
+    Builtin { id: "d", aliases: &[], extensions: &["d"], highlight: "d", display_name: "D", icon_class: GENERIC_ICON },
+    Builtin { id: "dart", aliases: &[], extensions: &["dart"], highlight: "dart", display_name: "Dart", icon_class: "devicon-dart-plain" },
+    Builtin { id: "diff", aliases: &[], extensions: &["diff", "patch"], highlight: "diff", display_name: "Diff", icon_class: "devicon-git-plain" },
+    Builtin { id: "dockerfile", aliases: &["docker"], extensions: &["dockerfile"], highlight: "dockerfile", display_name: "Dockerfile", icon_class: "devicon-docker-plain" },
+    Builtin { id: "elixir", aliases: &["ex", "exs"], extensions: &["ex", "exs"], highlight: "elixir", display_name: "Elixir", icon_class: "devicon-elixir-plain" },
+    Builtin { id: "elm", aliases: &[], extensions: &["elm"], highlight: "elm", display_name: "Elm", icon_class: "devicon-elm-plain" },
+    Builtin { id: "erlang", aliases: &["erl"], extensions: &["erl", "hrl"], highlight: "erlang", display_name: "Erlang", icon_class: "devicon-erlang-plain" },
+    Builtin { id: "fortran", aliases: &["f90", "f95"], extensions: &["f", "f90", "f95", "f03"], highlight: "fortran", display_name: "Fortran", icon_class: "devicon-fortran-original" },
+    Builtin { id: "fsharp", aliases: &["f#", "fs"], extensions: &["fs", "fsi", "fsx"], highlight: "fsharp", display_name: "F#", icon_class: "devicon-fsharp-plain" },
+    Builtin { id: "glsl", aliases: &[], extensions: &["glsl", "vert", "frag"], highlight: "glsl", display_name: "GLSL", icon_class: "devicon-opengl-plain" },
+    Builtin { id: "go", aliases: &["golang"], extensions: &["go"], highlight: "go", display_name: "Go", icon_class: "devicon-go-plain" },
+    Builtin { id: "graphql", aliases: &["gql"], extensions: &["graphql", "gql"], highlight: "graphql", display_name: "GraphQL", icon_class: "devicon-graphql-plain" },
+    Builtin { id: "groovy", aliases: &["gradle"], extensions: &["groovy", "gradle"], highlight: "groovy", display_name: "Groovy", icon_class: "devicon-groovy-plain" },
+    Builtin { id: "handlebars", aliases: &["hbs"], extensions: &["hbs", "handlebars"], highlight: "handlebars", display_name: "Handlebars", icon_class: "devicon-handlebars-plain" },
+    Builtin { id: "haskell", aliases: &["hs"], extensions: &["hs"], highlight: "haskell", display_name: "Haskell", icon_class: "devicon-haskell-plain" },
+    Builtin { id: "hcl", aliases: &["terraform", "tf"], extensions: &["hcl", "tf"], highlight: "hcl", display_name: "HCL", icon_class: "devicon-terraform-plain" },
+    Builtin { id: "html", aliases: &["htm", "xhtml"], extensions: &["html", "htm"], highlight: "xml", display_name: "HTML", icon_class: "devicon-html5-plain" },
+    Builtin { id: "http", aliases: &[], extensions: &["http"], highlight: "http", display_name: "HTTP", icon_class: GENERIC_ICON },
+    Builtin { id: "ini", aliases: &[], extensions: &["ini"], highlight: "ini", display_name: "INI", icon_class: GENERIC_ICON },
+    Builtin { id: "java", aliases: &[], extensions: &["java"], highlight: "java", display_name: "Java", icon_class: "devicon-java-plain" },
+    Builtin { id: "javascript", aliases: &["js", "jsx", "node"], extensions: &["js", "jsx", "mjs", "cjs"], highlight: "javascript", display_name: "JavaScript", icon_class: "devicon-javascript-plain" },
+    Builtin { id: "json", aliases: &[], extensions: &["json"], highlight: "json", display_name: "JSON", icon_class: "devicon-json-plain" },
+    Builtin { id: "julia", aliases: &[], extensions: &["jl"], highlight: "julia", display_name: "Julia", icon_class: "devicon-julia-plain" },
+    Builtin { id: "kotlin", aliases: &["kt"], extensions: &["kt", "kts"], highlight: "kotlin", display_name: "Kotlin", icon_class: "devicon-kotlin-plain" },
+    Builtin { id: "latex", aliases: &["tex"], extensions: &["tex"], highlight: "latex", display_name: "LaTeX", icon_class: "devicon-latex-original" },
+    Builtin { id: "less", aliases: &[], extensions: &["less"], highlight: "less", display_name: "Less", icon_class: "devicon-less-plain-wordmark" },
+    Builtin { id: "lisp", aliases: &["commonlisp"], extensions: &["lisp"], highlight: "lisp", display_name: "Lisp", icon_class: GENERIC_ICON },
+    Builtin { id: "lua", aliases: &[], extensions: &["lua"], highlight: "lua", display_name: "Lua", icon_class: "devicon-lua-plain" },
+    Builtin { id: "makefile", aliases: &["make"], extensions: &["mk", "mak"], highlight: "makefile", display_name: "Makefile", icon_class: "devicon-linux-plain" },
+    Builtin { id: "markdown", aliases: &["md"], extensions: &["md", "markdown"], highlight: "markdown", display_name: "Markdown", icon_class: "devicon-markdown-plain" },
+    Builtin { id: "matlab", aliases: &[], extensions: &[], highlight: "matlab", display_name: "MATLAB", icon_class: "devicon-matlab-plain" },
+    Builtin { id: "nginx", aliases: &[], extensions: &[], highlight: "nginx", display_name: "Nginx", icon_class: "devicon-nginx-plain" },
+    Builtin { id: "nim", aliases: &[], extensions: &["nim"], highlight: "nim", display_name: "Nim", icon_class: "devicon-nim-plain" },
+    Builtin { id: "nix", aliases: &[], extensions: &["nix"], highlight: "nix", display_name: "Nix", icon_class: "devicon-nixos-plain" },
+    Builtin { id: "objectivec", aliases: &["objc", "objective-c"], extensions: &["mm"], highlight: "objectivec", display_name: "Objective-C", icon_class: "devicon-apple-plain" },
+    Builtin { id: "ocaml", aliases: &["ml"], extensions: &["ml", "mli"], highlight: "ocaml", display_name: "OCaml", icon_class: "devicon-ocaml-plain" },
+    Builtin { id: "pascal", aliases: &["delphi", "pas"], extensions: &["pas", "dpr"], highlight: "delphi", display_name: "Pascal", icon_class: "devicon-delphi-plain" },
+    Builtin { id: "perl", aliases: &["pl"], extensions: &["pl", "pm"], highlight: "perl", display_name: "Perl", icon_class: "devicon-perl-plain" },
+    Builtin { id: "php", aliases: &[], extensions: &["php"], highlight: "php", display_name: "PHP", icon_class: "devicon-php-plain" },
+    Builtin { id: "plaintext", aliases: &["text", "txt"], extensions: &["txt"], highlight: "plaintext", display_name: "Plain Text", icon_class: GENERIC_ICON },
+    Builtin { id: "powershell", aliases: &["ps", "ps1", "pwsh"], extensions: &["ps1", "psm1"], highlight: "powershell", display_name: "PowerShell", icon_class: "devicon-powershell-plain" },
+    Builtin { id: "prolog", aliases: &[], extensions: &[], highlight: "prolog", display_name: "Prolog", icon_class: "devicon-prolog-plain" },
+    Builtin { id: "properties", aliases: &["props"], extensions: &["properties"], highlight: "properties", display_name: "Properties", icon_class: GENERIC_ICON },
+    Builtin { id: "protobuf", aliases: &["proto"], extensions: &["proto"], highlight: "protobuf", display_name: "Protocol Buffers", icon_class: GENERIC_ICON },
+    Builtin { id: "python", aliases: &["py", "python3"], extensions: &["py", "pyi"], highlight: "python", display_name: "Python", icon_class: "devicon-python-plain" },
+    Builtin { id: "r", aliases: &[], extensions: &["r"], highlight: "r", display_name: "R", icon_class: "devicon-r-plain" },
+    Builtin { id: "racket", aliases: &["rkt"], extensions: &["rkt"], highlight: "scheme", display_name: "Racket", icon_class: GENERIC_ICON },
+    Builtin { id: "ruby", aliases: &["rb"], extensions: &["rb"], highlight: "ruby", display_name: "Ruby", icon_class: "devicon-ruby-plain" },
+    Builtin { id: "rust", aliases: &["rs"], extensions: &["rs"], highlight: "rust", display_name: "Rust", icon_class: "devicon-rust-plain" },
+    Builtin { id: "scala", aliases: &[], extensions: &["scala", "sc"], highlight: "scala", display_name: "Scala", icon_class: "devicon-scala-plain" },
+    Builtin { id: "scheme", aliases: &["scm"], extensions: &["scm", "ss"], highlight: "scheme", display_name: "Scheme", icon_class: GENERIC_ICON },
+    Builtin { id: "scss", aliases: &[], extensions: &["scss"], highlight: "scss", display_name: "SCSS", icon_class: "devicon-sass-plain" },
+    Builtin { id: "shell", aliases: &["sh", "zsh", "console"], extensions: &["sh", "zsh"], highlight: "bash", display_name: "Shell", icon_class: "devicon-bash-plain" },
+    Builtin { id: "solidity", aliases: &["sol"], extensions: &["sol"], highlight: "solidity", display_name: "Solidity", icon_class: "devicon-solidity-plain" },
+    Builtin { id: "sql", aliases: &[], extensions: &["sql"], highlight: "sql", display_name: "SQL", icon_class: GENERIC_ICON },
+    Builtin { id: "svelte", aliases: &[], extensions: &["svelte"], highlight: "xml", display_name: "Svelte", icon_class: "devicon-svelte-plain" },
+    Builtin { id: "swift", aliases: &[], extensions: &["swift"], highlight: "swift", display_name: "Swift", icon_class: "devicon-swift-plain" },
+    Builtin { id: "toml", aliases: &[], extensions: &["toml"], highlight: "ini", display_name: "TOML", icon_class: GENERIC_ICON },
+    Builtin { id: "typescript", aliases: &["ts", "tsx"], extensions: &["ts", "tsx", "mts", "cts"], highlight: "typescript", display_name: "TypeScript", icon_class: "devicon-typescript-plain" },
+    Builtin { id: "vbnet", aliases: &["vb", "visualbasic"], extensions: &["vb"], highlight: "vbnet", display_name: "Visual Basic", icon_class: "devicon-visualbasic-plain" },
+    Builtin { id: "vim", aliases: &["vimscript"], extensions: &["vim"], highlight: "vim", display_name: "Vim Script", icon_class: "devicon-vim-plain" },
+    Builtin { id: "vue", aliases: &[], extensions: &["vue"], highlight: "xml", display_name: "Vue", icon_class: "devicon-vuejs-plain" },
+    Builtin { id: "wasm", aliases: &["wat", "wast"], extensions: &["wat", "wast"], highlight: "wasm", display_name: "WebAssembly", icon_class: "devicon-wasm-original" },
+    Builtin { id: "x86asm", aliases: &["nasm", "asm"], extensions: &["asm"], highlight: "x86asm", display_name: "x86 Assembly", icon_class: GENERIC_ICON },
+    Builtin { id: "xml", aliases: &[], extensions: &["xml"], highlight: "xml", display_name: "XML", icon_class: "devicon-html5-plain" },
+    Builtin { id: "yaml", aliases: &["yml"], extensions: &["yaml", "yml"], highlight: "yaml", display_name: "YAML", icon_class: GENERIC_ICON },
+    Builtin { id: "zig", aliases: &[], extensions: &["zig"], highlight: "zig", display_name: "Zig", icon_class: "devicon-zig-original" },
+];
+
+/// A language as it appears in tabs: a built-in one, possibly adjusted in
+/// `book.toml`, or one defined entirely there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+    pub id: String,
+    pub aliases: Vec<String>,
+    /// File extensions without the leading dot
+    pub extensions: Vec<String>,
+    /// highlight.js name of the language
+    pub highlight: String,
+    /// highlight.js name that code blocks are tagged with instead of the name they
+    /// were written with, set by `highlight-as`
+    pub highlight_as: Option<String>,
+    pub display_name: String,
+    pub icon_class: String,
+    /// Whether the language is in the registry, rather than made up from an unknown name
+    pub known: bool,
+}
+
+impl Language {
+    /// A language that isn't in the registry, named as written.
+    fn unknown(name: &str) -> Self {
+        Language {
+            id: name.to_string(),
+            aliases: Vec::new(),
+            extensions: Vec::new(),
+            highlight: name.to_string(),
+            highlight_as: None,
+            display_name: name.to_string(),
+            icon_class: GENERIC_ICON.to_string(),
+            known: false,
+        }
+    }
+}
+
+impl From<&Builtin> for Language {
+    fn from(builtin: &Builtin) -> Self {
+        Language {
+            id: builtin.id.to_string(),
+            aliases: builtin.aliases.iter().map(|s| s.to_string()).collect(),
+            extensions: builtin.extensions.iter().map(|s| s.to_string()).collect(),
+            highlight: builtin.highlight.to_string(),
+            highlight_as: None,
+            display_name: builtin.display_name.to_string(),
+            icon_class: builtin.icon_class.to_string(),
+            known: true,
+        }
+    }
+}
+
+/// Error returned when parsing a name that isn't a built-in language.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ProgrammingLanguage {
-    Apache,
-    ArmAssembly,
-    Bash,
-    C,
-    CoffeeScript,
-    Cpp,
-    CSharp,
-    Css,
-    D,
-    Diff,
-    Go,
-    Handlebars,
-    Haskell,
-    Http,
-    Ini,
-    Java,
-    JavaScript,
-    Json,
-    Julia,
-    Kotlin,
-    Less,
-    Lua,
-    Makefile,
-    Markdown,
-    Nginx,
-    Nim,
-    Nix,
-    ObjectiveC,
-    Perl,
-    Php,
-    PlainText,
-    Properties,
-    Python,
-    R,
-    Ruby,
-    Rust,
-    Scala,
-    Scss,
-    Shell,
-    Sql,
-    Swift,
-    TypeScript,
-    X86Assembly,
-    Xml,
-    Yaml,
-    Unknown(String),
+pub struct UnknownLanguage(pub String);
+
+impl fmt::Display for UnknownLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown language `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLanguage {}
+
+/// Parses a built-in language from its id or an alias, e.g. "cs" -> C#.
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        LanguageRegistry::builtin()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| UnknownLanguage(name.to_string()))
+    }
+}
+
+/// Formats as the language id, which parses back to the same language.
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id)
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The languages tabs know about, looked up by id, alias or file extension.
+#[derive(Debug, Clone)]
+pub struct LanguageRegistry {
+    languages: Vec<Language>,
+    /// Lowercase ids and aliases to indices into `languages`
+    names: HashMap<String, usize>,
+    /// Lowercase file extensions to indices into `languages`
+    extensions: HashMap<String, usize>,
 }
 
-impl ProgrammingLanguage {
-    pub fn from_str(lang: &str) -> Self {
-        match lang.to_lowercase().as_str() {
-            "apache" => Self::Apache,
-            "armasm" => Self::ArmAssembly,
-            "bash" => Self::Bash,
-            "c" => Self::C,
-            "coffeescript" => Self::CoffeeScript,
-            "cpp" => Self::Cpp,
-            "csharp" | "cs" => Self::CSharp,
-            "css" => Self::Css,
-            "d" => Self::D,
-            "diff" => Self::Diff,
-            "go" => Self::Go,
-            "handlebars" | "hbs" => Self::Handlebars,
-            "haskell" | "hs" => Self::Haskell,
-            "http" => Self::Http,
-            "ini" => Self::Ini,
-            "java" => Self::Java,
-            "javascript" | "js" => Self::JavaScript,
-            "json" => Self::Json,
-            "julia" => Self::Julia,
-            "kotlin" | "kt" => Self::Kotlin,
-            "less" => Self::Less,
-            "lua" => Self::Lua,
-            "makefile" | "make" => Self::Makefile,
-            "markdown" | "md" => Self::Markdown,
-            "nginx" => Self::Nginx,
-            "nim" => Self::Nim,
-            "nix" => Self::Nix,
-            "objectivec" | "objc" => Self::ObjectiveC,
-            "perl" | "pl" => Self::Perl,
-            "php" => Self::Php,
-            "plaintext" | "text" | "txt" => Self::PlainText,
-            "properties" | "props" => Self::Properties,
-            "python" | "py" => Self::Python,
-            "r" => Self::R,
-            "ruby" | "rb" => Self::Ruby,
-            "rust" | "rs" => Self::Rust,
-            "scala" => Self::Scala,
-            "scss" => Self::Scss,
-            "shell" | "sh" => Self::Shell,
-            "sql" => Self::Sql,
-            "swift" => Self::Swift,
-            "typescript" | "ts" => Self::TypeScript,
-            "x86asm" => Self::X86Assembly,
-            "xml" => Self::Xml,
-            "yaml" | "yml" => Self::Yaml,
-            _ => Self::Unknown(lang.to_string()),
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl LanguageRegistry {
+    pub fn builtin() -> Self {
+        let mut registry = LanguageRegistry {
+            languages: Vec::with_capacity(BUILTIN_LANGUAGES.len()),
+            names: HashMap::new(),
+            extensions: HashMap::new(),
+        };
+        for builtin in BUILTIN_LANGUAGES {
+            registry.register(builtin.into());
+        }
+        registry
+    }
+
+    /// The built-in languages with the definitions from `book.toml` merged over them.
+    pub fn with_custom(custom: &BTreeMap<String, CustomLanguage>) -> Self {
+        let mut registry = Self::builtin();
+        for (id, definition) in custom {
+            let mut language = registry
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| Language::unknown(&id.to_lowercase()));
+
+            language.aliases.extend(definition.aliases.iter().cloned());
+            if let Some(display_name) = &definition.display_name {
+                language.display_name = display_name.clone();
+            }
+            if let Some(icon) = &definition.icon {
+                language.icon_class = icon.clone();
+            }
+            language.known = true;
+
+            registry.register(language);
+        }
+
+        // `highlight-as` names a language of the registry, which may be defined later
+        for (id, definition) in custom {
+            let Some(highlight_as) = &definition.highlight_as else {
+                continue;
+            };
+            let highlight = registry
+                .get(highlight_as)
+                .map_or_else(|| highlight_as.clone(), |target| target.highlight.clone());
+            let index = registry.names[&id.to_lowercase()];
+            registry.languages[index].highlight = highlight.clone();
+            registry.languages[index].highlight_as = Some(highlight);
+        }
+        registry
+    }
+
+    /// Add a language, replacing the one with the same id. Its aliases and extensions
+    /// take precedence over those of languages registered before.
+    pub fn register(&mut self, language: Language) {
+        let index = match self.languages.iter().position(|l| l.id == language.id) {
+            Some(index) => {
+                self.languages[index] = language;
+                index
+            }
+            None => {
+                self.languages.push(language);
+                self.languages.len() - 1
+            }
+        };
+
+        let language = &self.languages[index];
+        for name in std::iter::once(&language.id).chain(&language.aliases) {
+            self.names.insert(name.to_lowercase(), index);
         }
+        for extension in &language.extensions {
+            self.extensions.insert(extension.to_lowercase(), index);
+        }
+    }
+
+    /// Look up a language by id or alias.
+    pub fn get(&self, name: &str) -> Option<&Language> {
+        self.names
+            .get(&name.to_lowercase())
+            .map(|&index| &self.languages[index])
+    }
+
+    /// Look up a language by file extension, with or without the leading dot.
+    pub fn by_extension(&self, extension: &str) -> Option<&Language> {
+        self.extensions
+            .get(&extension.trim_start_matches('.').to_lowercase())
+            .map(|&index| &self.languages[index])
+    }
+
+    /// Aliases and extensions with the id they resolve to, e.g. "ts" -> "typescript".
+    pub fn aliases(&self) -> BTreeMap<String, String> {
+        // Names take precedence over extensions, as in `resolve`
+        self.extensions
+            .iter()
+            .chain(&self.names)
+            .map(|(name, &index)| (name.clone(), self.languages[index].id.clone()))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .filter(|(name, id)| name != id)
+            .collect()
+    }
+
+    /// Look up a language by id, alias or extension, making up an unknown one if
+    /// none matches.
+    pub fn resolve(&self, name: &str) -> Language {
+        self.get(name)
+            .or_else(|| self.by_extension(name))
+            .cloned()
+            .unwrap_or_else(|| Language::unknown(name))
     }
+}
 
-    pub fn to_identifier(&self) -> String {
-        match self {
-            Self::Apache => "apache",
-            Self::ArmAssembly => "armasm",
-            Self::Bash => "bash",
-            Self::C => "c",
-            Self::CoffeeScript => "coffeescript",
-            Self::Cpp => "cpp",
-            Self::CSharp => "csharp",
-            Self::Css => "css",
-            Self::D => "d",
-            Self::Diff => "diff",
-            Self::Go => "go",
-            Self::Handlebars => "handlebars",
-            Self::Haskell => "haskell",
-            Self::Http => "http",
-            Self::Ini => "ini",
-            Self::Java => "java",
-            Self::JavaScript => "javascript",
-            Self::Json => "json",
-            Self::Julia => "julia",
-            Self::Kotlin => "kotlin",
-            Self::Less => "less",
-            Self::Lua => "lua",
-            Self::Makefile => "makefile",
-            Self::Markdown => "markdown",
-            Self::Nginx => "nginx",
-            Self::Nim => "nim",
-            Self::Nix => "nix",
-            Self::ObjectiveC => "objectivec",
-            Self::Perl => "perl",
-            Self::Php => "php",
-            Self::PlainText => "plaintext",
-            Self::Properties => "properties",
-            Self::Python => "python",
-            Self::R => "r",
-            Self::Ruby => "ruby",
-            Self::Rust => "rust",
-            Self::Scala => "scala",
-            Self::Scss => "scss",
-            Self::Shell => "shell",
-            Self::Sql => "sql",
-            Self::Swift => "swift",
-            Self::TypeScript => "typescript",
-            Self::X86Assembly => "x86asm",
-            Self::Xml => "xml",
-            Self::Yaml => "yaml",
-            Self::Unknown(lang) => lang,
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn names_are_unique() {
+        let mut seen = HashSet::new();
+        for builtin in BUILTIN_LANGUAGES {
+            for name in std::iter::once(&builtin.id).chain(builtin.aliases) {
+                assert!(
+                    seen.insert(name.to_lowercase()),
+                    "`{}` names more than one language",
+                    name
+                );
+            }
         }
-        .to_string()
-    }
-
-    pub fn display_name(&self) -> String {
-        match self {
-            Self::Apache => "Apache".to_string(),
-            Self::ArmAssembly => "ARM Assembly".to_string(),
-            Self::Bash => "Bash".to_string(),
-            Self::C => "C".to_string(),
-            Self::CoffeeScript => "CoffeeScript".to_string(),
-            Self::Cpp => "C++".to_string(),
-            Self::CSharp => "C#".to_string(),
-            Self::Css => "CSS".to_string(),
-            Self::D => "D".to_string(),
-            Self::Diff => "Diff".to_string(),
-            Self::Go => "Go".to_string(),
-            Self::Handlebars => "Handlebars".to_string(),
-            Self::Haskell => "Haskell".to_string(),
-            Self::Http => "HTTP".to_string(),
-            Self::Ini => "INI".to_string(),
-            Self::Java => "Java".to_string(),
-            Self::JavaScript => "JavaScript".to_string(),
-            Self::Json => "JSON".to_string(),
-            Self::Julia => "Julia".to_string(),
-            Self::Kotlin => "Kotlin".to_string(),
-            Self::Less => "Less".to_string(),
-            Self::Lua => "Lua".to_string(),
-            Self::Makefile => "Makefile".to_string(),
-            Self::Markdown => "Markdown".to_string(),
-            Self::Nginx => "Nginx".to_string(),
-            Self::Nim => "Nim".to_string(),
-            Self::Nix => "Nix".to_string(),
-            Self::ObjectiveC => "Objective-C".to_string(),
-            Self::Perl => "Perl".to_string(),
-            Self::Php => "PHP".to_string(),
-            Self::PlainText => "Plain Text".to_string(),
-            Self::Properties => "Properties".to_string(),
-            Self::Python => "Python".to_string(),
-            Self::R => "R".to_string(),
-            Self::Ruby => "Ruby".to_string(),
-            Self::Rust => "Rust".to_string(),
-            Self::Scala => "Scala".to_string(),
-            Self::Scss => "SCSS".to_string(),
-            Self::Shell => "Shell".to_string(),
-            Self::Sql => "SQL".to_string(),
-            Self::Swift => "Swift".to_string(),
-            Self::TypeScript => "TypeScript".to_string(),
-            Self::X86Assembly => "x86 Assembly".to_string(),
-            Self::Xml => "XML".to_string(),
-            Self::Yaml => "YAML".to_string(),
-            Self::Unknown(lang) => lang.clone(),
+    }
+
+    #[test]
+    fn aliases_round_trip() {
+        for builtin in BUILTIN_LANGUAGES {
+            for alias in builtin.aliases {
+                let language: Language = alias.parse().unwrap();
+                assert_eq!(language.id, builtin.id);
+                assert_eq!(language.to_string().parse::<Language>(), Ok(language));
+            }
         }
     }
 
-    pub fn icon_class(&self) -> String {
-        match self {
-            Self::Apache => "devicon-apache-plain",
-            Self::ArmAssembly | Self::X86Assembly => "devicon-devicon-plain", // Generic icon fallback
-            Self::Bash | Self::Shell => "devicon-bash-plain",
-            Self::C => "devicon-c-plain",
-            Self::CoffeeScript => "devicon-coffeescript-plain",
-            Self::Cpp => "devicon-cplusplus-plain",
-            Self::CSharp => "devicon-csharp-plain",
-            Self::Css => "devicon-css3-plain",
-            Self::D => "devicon-d3js-plain",
-            Self::Diff => "devicon-git-plain",
-            Self::Go => "devicon-go-plain",
-            Self::Handlebars => "devicon-handlebars-plain",
-            Self::Haskell => "devicon-haskell-plain",
-            Self::Http => "devicon-chrome-plain",
-            Self::Ini | Self::Properties => "devicon-devicon-plain",
-            Self::Java => "devicon-java-plain",
-            Self::JavaScript | Self::Json => "devicon-javascript-plain",
-            Self::Julia => "devicon-julia-plain",
-            Self::Kotlin => "devicon-kotlin-plain",
-            Self::Less => "devicon-less-plain-wordmark",
-            Self::Lua => "devicon-lua-plain",
-            Self::Makefile => "devicon-linux-plain",
-            Self::Markdown => "devicon-markdown-plain",
-            Self::Nginx => "devicon-nginx-plain",
-            Self::Nim => "devicon-nim-plain",
-            Self::Nix => "devicon-nixos-plain",
-            Self::ObjectiveC => "devicon-apple-plain",
-            Self::Perl => "devicon-perl-plain",
-            Self::Php => "devicon-php-plain",
-            Self::PlainText => "devicon-devicon-plain",
-            Self::Python => "devicon-python-plain",
-            Self::R => "devicon-r-plain",
-            Self::Ruby => "devicon-ruby-plain",
-            Self::Rust => "devicon-rust-plain",
-            Self::Scala => "devicon-scala-plain",
-            Self::Scss => "devicon-sass-plain",
-            Self::Sql => "devicon-mysql-plain",
-            Self::Swift => "devicon-swift-plain",
-            Self::TypeScript => "devicon-typescript-plain",
-            Self::Xml => "devicon-html5-plain",
-            Self::Yaml => "devicon-devicon-plain",
-            Self::Unknown(_) => "devicon-devicon-plain",
+    #[test]
+    fn aliases_resolve_like_the_registry() {
+        let registry = LanguageRegistry::builtin();
+        let aliases = registry.aliases();
+        assert_eq!(aliases["ts"], "typescript");
+        assert_eq!(aliases["golang"], "go");
+        assert_eq!(aliases["pyi"], "python");
+        assert!(!aliases.contains_key("rust"));
+        for (name, id) in &aliases {
+            assert_eq!(&registry.resolve(name).id, id);
         }
-        .to_string()
     }
 }
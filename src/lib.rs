@@ -2,9 +2,13 @@ use mdbook::book::{Book, BookItem};
 use mdbook::errors::Error;
 use mdbook::preprocess::{Preprocessor, PreprocessorContext};
 
+mod config;
+mod icons;
 mod languages;
 mod preprocessor;
 
+use config::LangTabsConfig;
+
 pub struct LangTabsPreprocessor;
 
 impl Default for LangTabsPreprocessor {
@@ -24,17 +28,36 @@ impl Preprocessor for LangTabsPreprocessor {
         "langtabs"
     }
 
-    fn run(&self, _ctx: &PreprocessorContext, mut book: Book) -> Result<Book, Error> {
+    fn run(&self, ctx: &PreprocessorContext, mut book: Book) -> Result<Book, Error> {
+        let config = LangTabsConfig::from_context(ctx)?;
+        let style = config.output_style(&ctx.renderer);
+
+        // Collect the languages used across the book for the page-level switcher
+        let mut languages = Vec::new();
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item {
+                preprocessor::collect_languages(&chapter.content, &config, &mut languages);
+            }
+        }
+        let page_config = config.page_config(languages);
+
+        let mut result = Ok(());
         book.for_each_mut(|item| {
+            if result.is_err() {
+                return;
+            }
             if let BookItem::Chapter(chapter) = item {
-                preprocessor::process_chapter(chapter);
+                result = preprocessor::process_chapter(chapter, &config, &page_config, style);
             }
         });
+        result?;
 
         Ok(book)
     }
 
-    fn supports_renderer(&self, renderer: &str) -> bool {
-        renderer == "html"
+    // Other renderers get the groups as plain code blocks, see `output`. Books can
+    // limit the renderers with mdBook's `renderers` key.
+    fn supports_renderer(&self, _renderer: &str) -> bool {
+        true
     }
 }
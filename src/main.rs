@@ -59,9 +59,13 @@ fn main() {
         // Normal preprocessing mode
         let (ctx, book) = CmdPreprocessor::parse_input(io::stdin()).expect("Failed to parse stdin");
 
-        let processed_book = preprocessor
-            .run(&ctx, book)
-            .expect("Failed to process book");
+        let processed_book = match preprocessor.run(&ctx, book) {
+            Ok(book) => book,
+            Err(e) => {
+                eprintln!("Error processing book: {}", e);
+                process::exit(1);
+            }
+        };
 
         serde_json::to_writer(io::stdout(), &processed_book)
             .expect("Failed to write processed book");
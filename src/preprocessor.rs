@@ -1,75 +1,579 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
 use mdbook::book::Chapter;
+use mdbook::errors::Error;
+use pulldown_cmark::{Event, Parser, Tag};
 use regex::Regex;
 
-use crate::languages;
+use crate::config::{
+    DEFAULT_NAMESPACE, IconSource, LabelMode, LangTabsConfig, LanguageFilter, Layout, OutputStyle,
+    PageConfig, PageLanguage, Print, PrintMode, View,
+};
+use crate::icons;
+use crate::languages::Language;
+
+// Options accepted on the start marker of a group
+const GROUP_ATTRIBUTES: &[&str] = &[
+    "id",
+    "namespace",
+    "equal-height",
+    "sticky",
+    "labels",
+    "layout",
+    "view",
+    "view-toggle",
+];
+// File types that icons can point at instead of naming an icon class
+const IMAGE_EXTENSIONS: &[&str] = &[".svg", ".png", ".jpg", ".jpeg", ".gif", ".webp"];
+// Options accepted on a tab marker
+const TAB_ATTRIBUTES: &[&str] = &["label", "icon"];
+
+/// Replace the language tab groups of a chapter. In strict mode, malformed groups
+/// fail the build.
+pub fn process_chapter(
+    chapter: &mut Chapter,
+    config: &LangTabsConfig,
+    page_config: &PageConfig,
+    style: OutputStyle,
+) -> Result<(), Error> {
+    let location = ChapterLocation::new(chapter);
+    let mut problems = Vec::new();
+    chapter.content = process_markdown(
+        &chapter.content,
+        config,
+        page_config,
+        style,
+        &location,
+        &mut problems,
+    );
+
+    if config.strict && !problems.is_empty() {
+        return Err(Error::msg(format!(
+            "Invalid language tabs in chapter \"{}\":\n  {}",
+            chapter.name,
+            problems.join("\n  ")
+        )));
+    }
+
+    Ok(())
+}
+
+// Where a chapter is in the book
+struct ChapterLocation {
+    // Relative path from the chapter to the book root, for icon images
+    path_to_root: String,
+    // Keeps group ids unique when all chapters are combined on the print page
+    slug: String,
+}
 
-pub fn process_chapter(chapter: &mut Chapter) {
-    chapter.content = process_markdown(&chapter.content);
+impl ChapterLocation {
+    fn new(chapter: &Chapter) -> Self {
+        match &chapter.path {
+            Some(path) => ChapterLocation {
+                path_to_root: "../".repeat(path.components().count().saturating_sub(1)),
+                slug: slugify(&path.with_extension("").to_string_lossy()),
+            },
+            // Draft chapters have no file
+            None => ChapterLocation {
+                path_to_root: String::new(),
+                slug: slugify(&chapter.name),
+            },
+        }
+    }
 }
 
-pub fn process_markdown(content: &str) -> String {
-    let start_comment = "<!-- langtabs-start -->";
-    let end_comment = "<!-- langtabs-end -->";
+fn process_markdown(
+    content: &str,
+    config: &LangTabsConfig,
+    page_config: &PageConfig,
+    style: OutputStyle,
+    location: &ChapterLocation,
+    problems: &mut Vec<String>,
+) -> String {
+    let result = process_groups(content, config, style, location, problems);
+    let result = process_only_blocks(&result, config, style);
+    let mut result = process_terms(&result, config, style);
+
+    // Every page gets the settings, since `langtabs-only` regions, terms and the
+    // menu bar controls depend on them as much as groups do
+    if style == OutputStyle::Tabs {
+        result.push_str(&page_config.to_html());
+    }
+
+    result
+}
 
-    if !content.contains(start_comment) || !content.contains(end_comment) {
+// Replace tab groups with their HTML
+fn process_groups(
+    content: &str,
+    config: &LangTabsConfig,
+    style: OutputStyle,
+    location: &ChapterLocation,
+    problems: &mut Vec<String>,
+) -> String {
+    if !content.contains(&config.start_comment()) {
         return content.to_string();
     }
 
-    // Find all language tab blocks
-    let re = Regex::new(&format!(
-        r"{}([\s\S]*?){}",
-        regex::escape(start_comment),
-        regex::escape(end_comment)
-    ))
+    let re = group_regex(config);
+
+    let mut result = String::with_capacity(content.len());
+    let mut last_end = 0;
+    let mut group_count = 0;
+
+    for (index, cap) in re.captures_iter(content).enumerate() {
+        group_count += 1;
+        let full_match = cap.get(0).unwrap();
+        let attributes = parse_attributes(cap.get(1).map_or("", |m| m.as_str()));
+        let inner_content = cap.get(2).unwrap().as_str();
+        check_attributes(
+            &attributes,
+            GROUP_ATTRIBUTES,
+            &config.start_marker,
+            problems,
+        );
+
+        let mut lang_sections = extract_language_sections(inner_content, config, problems);
+        if lang_sections.is_empty() {
+            problems.push(format!("group {} contains no code blocks", index + 1));
+            continue;
+        }
+
+        let options = GroupOptions::from_attributes(&attributes, index, location, config, problems);
+        let output = if style == OutputStyle::Tabs {
+            if options.namespace == DEFAULT_NAMESPACE {
+                add_placeholder_sections(&mut lang_sections, config);
+            }
+            order_sections(&mut lang_sections, config);
+            generate_tabs_html(&options, &lang_sections, config, location)
+        } else {
+            order_sections(&mut lang_sections, config);
+            generate_sequential(&lang_sections, style)
+        };
+
+        result.push_str(&content[last_end..full_match.start()]);
+        result.push_str(&output);
+        last_end = full_match.end();
+    }
+    result.push_str(&content[last_end..]);
+
+    if content.matches(&config.start_comment()).count() > group_count {
+        problems.push(format!(
+            "a group is missing its `{}` marker",
+            config.end_comment()
+        ));
+    }
+
+    result
+}
+
+// Wrap `<!-- langtabs-only rust,go -->` regions so they are only shown to readers
+// who prefer one of the listed languages. Other renderers get a note naming them.
+fn process_only_blocks(content: &str, config: &LangTabsConfig, style: OutputStyle) -> String {
+    if !content.contains("<!-- langtabs-only") {
+        return content.to_string();
+    }
+
+    let re = Regex::new(
+        r"<!--\s*langtabs-only\s+([^>]*?)\s*-->([\s\S]*?)<!--\s*langtabs-only-end\s*-->",
+    )
     .unwrap();
 
-    let mut result = content.to_string();
+    replace_outside_code(content, &re, |cap| {
+        let languages: Vec<Language> = cap[1]
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| config.language(name))
+            .collect();
+
+        let keys: Vec<&str> = languages.iter().map(|l| l.id.as_str()).collect();
+        let names: Vec<&str> = languages.iter().map(|l| l.display_name.as_str()).collect();
+
+        match style {
+            OutputStyle::Tabs => {}
+            OutputStyle::Headings => {
+                return format!(
+                    "*{} only:*\n\n{}",
+                    names.join(", "),
+                    cap[2].trim_matches('\n')
+                );
+            }
+            OutputStyle::Plain => return cap[2].trim_matches('\n').to_string(),
+        }
+
+        format!(
+            "<div class=\"langtabs-only\" data-langs=\"{}\">\n<span class=\"langtabs-only-badge\">{}</span>\n\n{}\n\n</div>",
+            escape_html(&keys.join(" ")),
+            escape_html(&names.join(", ")),
+            cap[2].trim_matches('\n')
+        )
+    })
+}
 
-    for cap in re.captures_iter(content) {
-        let full_match = cap.get(0).unwrap().as_str();
-        let inner_content = cap.get(1).unwrap().as_str();
+// Replace the matches of `re` that don't start inside a code block or code span,
+// where markers and terms are examples to be shown as written
+fn replace_outside_code(
+    content: &str,
+    re: &Regex,
+    mut replace: impl FnMut(&regex::Captures) -> String,
+) -> String {
+    let code: Vec<Range<usize>> = Parser::new(content)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Code(_) => Some(range),
+            _ => None,
+        })
+        .collect();
 
-        let lang_sections = extract_language_sections(inner_content);
-        if !lang_sections.is_empty() {
-            let html = generate_tabs_html(&lang_sections);
-            result = result.replace(full_match, &html);
+    let mut result = String::with_capacity(content.len());
+    let mut last_end = 0;
+    let mut position = 0;
+    while let Some(cap) = re.captures_at(content, position) {
+        let full_match = cap.get(0).unwrap();
+        if code.iter().any(|range| range.contains(&full_match.start())) {
+            // Look for a later match, which may start inside this one
+            position = full_match.start()
+                + content[full_match.start()..]
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+            continue;
         }
+
+        result.push_str(&content[last_end..full_match.start()]);
+        result.push_str(&replace(&cap));
+        last_end = full_match.end();
+        position = full_match.end();
     }
+    result.push_str(&content[last_end..]);
 
     result
 }
 
+/// Add the languages of the default namespace groups in `content` to `languages`,
+/// in order of first appearance.
+pub fn collect_languages(
+    content: &str,
+    config: &LangTabsConfig,
+    languages: &mut Vec<PageLanguage>,
+) {
+    if !content.contains(&config.start_comment()) {
+        return;
+    }
+
+    for cap in group_regex(config).captures_iter(content) {
+        let attributes = parse_attributes(cap.get(1).map_or("", |m| m.as_str()));
+        let in_default_namespace = attributes
+            .get("namespace")
+            .is_none_or(|namespace| namespace == DEFAULT_NAMESPACE);
+        if !in_default_namespace {
+            continue;
+        }
+
+        // Problems are reported when the chapter itself is processed
+        let sections =
+            extract_language_sections(cap.get(2).unwrap().as_str(), config, &mut Vec::new());
+        for section in sections {
+            let key = section.key();
+            if !languages.iter().any(|language| language.key == key) {
+                languages.push(PageLanguage {
+                    key,
+                    name: section.display_name(),
+                });
+            }
+        }
+    }
+}
+
+// Expand inline terms like `{{lt rust="new_client()" js="newClient()"}}` into spans
+// that follow the reader's language. Until a language is picked all variants are
+// listed, each with its language name.
+fn process_terms(content: &str, config: &LangTabsConfig, style: OutputStyle) -> String {
+    if !content.contains("{{lt ") {
+        return content.to_string();
+    }
+
+    let re = Regex::new(r#"\{\{lt((?:\s+[a-zA-Z][a-zA-Z0-9_-]*="[^"]*")+)\s*\}\}"#).unwrap();
+
+    replace_outside_code(content, &re, |cap| {
+        let attributes = parse_attribute_list(&cap[1]);
+
+        // Without script, list every variant as text
+        if style != OutputStyle::Tabs {
+            let variants: Vec<String> = attributes
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "{} ({})",
+                        escape_markdown_html(value),
+                        config.language(name).display_name
+                    )
+                })
+                .collect();
+            return variants.join(" / ");
+        }
+
+        let variants: Vec<String> = attributes
+            .into_iter()
+            .map(|(name, value)| {
+                let language = config.language(&name);
+                format!(
+                    r#"<span class="langtabs-term-variant" data-lang="{}">{}<span class="langtabs-term-lang"> ({})</span></span>"#,
+                    escape_html(&language.id),
+                    escape_markdown_html(&value),
+                    escape_html(&language.display_name)
+                )
+            })
+            .collect();
+
+        format!(
+            r#"<span class="langtabs-term">{}</span>"#,
+            variants.join(r#"<span class="langtabs-term-sep"> / </span>"#)
+        )
+    })
+}
+
+// Find all language tab blocks, capturing the attributes of the start marker
+fn group_regex(config: &LangTabsConfig) -> Regex {
+    Regex::new(&format!(
+        r"{}(\s[^>]*)?-->([\s\S]*?){}",
+        regex::escape(&config.start_comment()),
+        regex::escape(&config.end_comment())
+    ))
+    .unwrap()
+}
+
+fn check_attributes(
+    attributes: &BTreeMap<String, String>,
+    known: &[&str],
+    marker: &str,
+    problems: &mut Vec<String>,
+) {
+    for name in attributes.keys() {
+        if !known.contains(&name.as_str()) {
+            problems.push(format!(
+                "unknown option `{}` on `{}` marker, expected one of: {}",
+                name,
+                marker,
+                known.join(", ")
+            ));
+        }
+    }
+}
+
+// Parse `key="value"` pairs from a marker comment
+fn parse_attributes(content: &str) -> BTreeMap<String, String> {
+    parse_attribute_list(content).into_iter().collect()
+}
+
+// Parse `key="value"` pairs, keeping their order
+fn parse_attribute_list(content: &str) -> Vec<(String, String)> {
+    let re = Regex::new(r#"([a-zA-Z][a-zA-Z0-9_-]*)="([^"]*)""#).unwrap();
+
+    re.captures_iter(content)
+        .map(|cap| (cap[1].to_lowercase(), cap[2].to_string()))
+        .collect()
+}
+
+// Options set on a group's start marker
+struct GroupOptions {
+    id: String,
+    namespace: String,
+    equal_height: bool,
+    sticky: bool,
+    labels: LabelMode,
+    layout: Layout,
+    view: View,
+    view_toggle: bool,
+}
+
+impl GroupOptions {
+    fn from_attributes(
+        attributes: &BTreeMap<String, String>,
+        index: usize,
+        location: &ChapterLocation,
+        config: &LangTabsConfig,
+        problems: &mut Vec<String>,
+    ) -> Self {
+        let flag = |name: &str, default: bool| {
+            attributes
+                .get(name)
+                .map(|value| value == "true")
+                .unwrap_or(default)
+        };
+
+        GroupOptions {
+            id: match attributes.get("id") {
+                Some(id) => format!("langtabs-{}-{}", location.slug, id),
+                None => format!("langtabs-{}-{}", location.slug, index),
+            },
+            namespace: attributes
+                .get("namespace")
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string()),
+            equal_height: flag("equal-height", config.equal_height),
+            sticky: flag("sticky", config.sticky_tabs),
+            labels: match attributes.get("labels") {
+                Some(value) => LabelMode::from_name(value).unwrap_or_else(|| {
+                    problems.push(format!(
+                        "unknown labels `{}`, expected one of: icon+text, text, icon",
+                        value
+                    ));
+                    config.labels
+                }),
+                None => config.labels,
+            },
+            layout: match attributes.get("layout") {
+                Some(value) => Layout::from_name(value).unwrap_or_else(|| {
+                    problems.push(format!(
+                        "unknown layout `{}`, expected one of: tabs, pills, vertical, auto",
+                        value
+                    ));
+                    config.layout
+                }),
+                None => config.layout,
+            },
+            view: match attributes.get("view") {
+                Some(value) => View::from_name(value).unwrap_or_else(|| {
+                    problems.push(format!(
+                        "unknown view `{}`, expected one of: tabbed, stacked, compare",
+                        value
+                    ));
+                    config.view
+                }),
+                None => config.view,
+            },
+            view_toggle: flag("view-toggle", config.view_toggle),
+        }
+    }
+}
+
 // A struct to represent a language section with its content
 struct LanguageSection {
-    language: languages::ProgrammingLanguage,
+    language: Language,
+    // Explicit tab label from a `<!-- langtabs-tab label="..." -->` marker
+    label: Option<String>,
+    icon: Option<String>,
     content: String,
+    // Stands in for a required language the group doesn't cover yet
+    placeholder: bool,
+}
+
+impl LanguageSection {
+    fn from_code_block(
+        language: Language,
+        attributes: &mut BTreeMap<String, String>,
+        lines: &[String],
+    ) -> Self {
+        LanguageSection {
+            language,
+            label: attributes.remove("label"),
+            icon: attributes.remove("icon"),
+            content: lines.join("\n"),
+            placeholder: false,
+        }
+    }
+
+    // The code block as written, or with its fence tagged with the language set by
+    // `highlight-as`, e.g. a DSL highlighted as its host language. Attributes after
+    // the language, as in `rust,ignore`, are kept.
+    fn highlighted_content(&self) -> String {
+        let Some(highlight) = self
+            .language
+            .highlight_as
+            .as_ref()
+            .filter(|_| !self.placeholder)
+        else {
+            return self.content.clone();
+        };
+
+        let (fence, rest) = self.content.split_once('\n').unwrap_or((&self.content, ""));
+        let Some(info) = fence.strip_prefix("```") else {
+            return self.content.clone();
+        };
+        let attributes = info.trim_start_matches(|c: char| c != ',' && !c.is_whitespace());
+
+        format!("```{}{}\n{}", highlight, attributes, rest)
+    }
+
+    // Key used to match tabs across groups and in preferences
+    fn key(&self) -> String {
+        match &self.label {
+            Some(label) => slugify(label),
+            None => self.language.id.clone(),
+        }
+    }
+
+    fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => self.language.display_name.clone(),
+        }
+    }
+
+    fn icon_class(&self) -> Option<String> {
+        match (&self.label, &self.icon) {
+            (_, Some(icon)) => Some(icon.clone()),
+            (Some(_), None) => None,
+            (None, None) => Some(self.language.icon_class.clone()),
+        }
+    }
 }
 
 // Extract language sections using a simple state machine
-fn extract_language_sections(content: &str) -> Vec<LanguageSection> {
+fn extract_language_sections(
+    content: &str,
+    config: &LangTabsConfig,
+    problems: &mut Vec<String>,
+) -> Vec<LanguageSection> {
     let mut sections: Vec<LanguageSection> = Vec::new();
 
     // Split the content into lines
     let lines: Vec<&str> = content.lines().collect();
 
     // Regex for detecting code block start and end
-    let start_block_regex = Regex::new(r"^```([a-zA-Z0-9_+-]+)\s*$").unwrap();
+    // The language may be followed by attributes, as in `rust,ignore` or `rust editable`
+    let start_block_regex = Regex::new(r"^```([a-zA-Z0-9_+#.-]+)(?:[,\s][^`]*)?$").unwrap();
     let end_block_regex = Regex::new(r"^```\s*$").unwrap();
+    let tab_marker_regex = Regex::new(&format!(
+        r"^\s*<!--\s*{}(\s[^>]*)?-->\s*$",
+        regex::escape(&config.tab_marker)
+    ))
+    .unwrap();
 
     // State machine variables
     let mut in_code_block = false;
     let mut current_language = String::new();
     let mut current_content = Vec::new();
+    let mut pending_attributes = BTreeMap::new();
 
     for line in lines {
         if !in_code_block {
+            // Check if this line labels the next code block
+            if let Some(captures) = tab_marker_regex.captures(line) {
+                pending_attributes = parse_attributes(captures.get(1).map_or("", |m| m.as_str()));
+                check_attributes(
+                    &pending_attributes,
+                    TAB_ATTRIBUTES,
+                    &config.tab_marker,
+                    problems,
+                );
+            }
             // Check if this line starts a code block
-            if let Some(captures) = start_block_regex.captures(line) {
+            else if let Some(captures) = start_block_regex.captures(line) {
                 in_code_block = true;
                 current_language = captures[1].to_lowercase();
                 current_content = vec![line.to_string()];
             }
             // Otherwise, ignore text outside of code blocks
+            else if !line.trim().is_empty() {
+                problems.push(format!(
+                    "text outside of code blocks is ignored: `{}`",
+                    line.trim()
+                ));
+            }
         } else {
             // We're in a code block, add the line
             current_content.push(line.to_string());
@@ -77,79 +581,272 @@ fn extract_language_sections(content: &str) -> Vec<LanguageSection> {
             // Check if this line ends the code block
             if end_block_regex.is_match(line) {
                 // Add the completed section
-                sections.push(LanguageSection {
-                    language: languages::ProgrammingLanguage::from_str(&current_language),
-                    content: current_content.join("\n"),
-                });
+                sections.push(LanguageSection::from_code_block(
+                    config.language(&current_language),
+                    &mut pending_attributes,
+                    &current_content,
+                ));
 
                 // Reset state
                 in_code_block = false;
                 current_language = String::new();
                 current_content = Vec::new();
+                pending_attributes = BTreeMap::new();
             }
         }
     }
 
     // In case the last block wasn't properly closed
     if in_code_block && !current_content.is_empty() {
-        sections.push(LanguageSection {
-            language: languages::ProgrammingLanguage::from_str(&current_language),
-            content: current_content.join("\n"),
-        });
+        problems.push(format!("unclosed `{}` code block", current_language));
+        sections.push(LanguageSection::from_code_block(
+            config.language(&current_language),
+            &mut pending_attributes,
+            &current_content,
+        ));
+    }
+
+    for section in &sections {
+        if section.label.is_none() && !section.language.known {
+            problems.push(format!("unknown language `{}`", section.language.id));
+        }
     }
 
     sections
 }
 
-fn generate_tabs_html(sections: &[LanguageSection]) -> String {
+// Sort tabs by the configured order. Unlisted tabs follow in their original order.
+fn order_sections(sections: &mut [LanguageSection], config: &LangTabsConfig) {
+    if config.order.is_empty() {
+        return;
+    }
+
+    let order: Vec<String> = config
+        .order
+        .iter()
+        .map(|name| config.tab_key(name))
+        .collect();
+    sections.sort_by_key(|section| {
+        order
+            .iter()
+            .position(|key| *key == section.key())
+            .unwrap_or(order.len())
+    });
+}
+
+// Append placeholder tabs for required languages the group doesn't cover
+fn add_placeholder_sections(sections: &mut Vec<LanguageSection>, config: &LangTabsConfig) {
+    for required in &config.required {
+        let language = config.language(required);
+        if sections.iter().any(|section| section.language == language) {
+            continue;
+        }
+
+        let mut content = format!(
+            r#"<div class="langtabs-placeholder-body"><p>{}</p>"#,
+            escape_html(
+                &config
+                    .placeholder_text
+                    .replace("{language}", &language.display_name)
+            )
+        );
+        if let Some(link) = &config.placeholder_link {
+            content.push_str(&format!(
+                r#"<p><a href="{}">{}</a></p>"#,
+                escape_html(link),
+                escape_html(&config.placeholder_link_text)
+            ));
+        }
+        content.push_str("</div>");
+
+        sections.push(LanguageSection {
+            language,
+            label: None,
+            icon: None,
+            content,
+            placeholder: true,
+        });
+    }
+}
+
+fn generate_tabs_html(
+    options: &GroupOptions,
+    sections: &[LanguageSection],
+    config: &LangTabsConfig,
+    location: &ChapterLocation,
+) -> String {
     if sections.is_empty() {
         return String::new();
     }
 
-    let mut html = r#"<div class="langtabs">"#.to_string();
+    let mut class = "langtabs".to_string();
+    if options.equal_height {
+        class.push_str(" langtabs-equal-height");
+    }
+    if options.sticky {
+        class.push_str(" langtabs-sticky");
+    }
+    if let Some(layout) = options.layout.class() {
+        class.push(' ');
+        class.push_str(layout);
+    }
+
+    // Tab shown before the reader picks one
+    let active = config
+        .default_tab
+        .as_ref()
+        .and_then(|name| {
+            let key = config.tab_key(name);
+            sections
+                .iter()
+                .position(|section| !section.placeholder && section.key() == key)
+        })
+        .unwrap_or(0);
+
+    // Panels printed below each other, unless only the active one is printed
+    let printed: Option<Vec<bool>> = match &config.print {
+        Print::Mode(PrintMode::Active) => None,
+        Print::Mode(PrintMode::All) => Some(sections.iter().map(|s| !s.placeholder).collect()),
+        Print::Languages(names) => {
+            let keys: Vec<String> = names.iter().map(|name| config.tab_key(name)).collect();
+            let printed: Vec<bool> = sections
+                .iter()
+                .map(|s| !s.placeholder && keys.contains(&s.key()))
+                .collect();
+            // Groups without any of the languages print their selected tab
+            printed.contains(&true).then_some(printed)
+        }
+    };
+    if printed.is_some() {
+        class.push_str(" langtabs-print-stacked");
+    }
+
+    let os_group = config.os_namespaces.contains(&options.namespace);
+
+    // Groups that can leave the tabbed view, or are printed stacked, need
+    // headings on their panels
+    let headings = options.view != View::Tabbed || options.view_toggle || printed.is_some();
+    let views = options.view != View::Tabbed || options.view_toggle;
+
+    let mut html = format!(
+        r#"<div class="{}" data-group="{}" data-namespace="{}"{}{}>"#,
+        class,
+        escape_html(&options.id),
+        escape_html(&options.namespace),
+        if os_group { " data-os-group" } else { "" },
+        if views {
+            format!(r#" data-view="{}""#, options.view.name())
+        } else {
+            String::new()
+        }
+    );
 
     // Generate tab headers
     html.push_str(r#"<div class="langtabs-header">"#);
     for (i, section) in sections.iter().enumerate() {
-        let class = if i == 0 {
-            "langtabs-tab active"
+        let mut class = if i == active {
+            "langtabs-tab active".to_string()
+        } else {
+            "langtabs-tab".to_string()
+        };
+        let mut extra_attributes = String::new();
+        if section.placeholder {
+            class.push_str(" langtabs-placeholder");
+            extra_attributes.push_str(r#" aria-disabled="true""#);
+        }
+        if os_group && let Some(os) = operating_system(&section.key(), config) {
+            extra_attributes.push_str(&format!(r#" data-os="{}""#, escape_html(os)));
+        }
+
+        let icon = section
+            .icon_class()
+            .filter(|_| config.icons != IconSource::None && options.labels != LabelMode::Text)
+            .map(|icon| icon_html(section, &icon, config, &location.path_to_root))
+            .unwrap_or_default();
+
+        // Show only the icon if there is one, keeping the name for tooltips and screen readers
+        let name = escape_html(&section.display_name());
+        let label = if options.labels == LabelMode::Icon && !icon.is_empty() {
+            extra_attributes.push_str(&format!(r#" title="{0}" aria-label="{0}""#, name));
+            String::new()
         } else {
-            "langtabs-tab"
+            name
         };
 
         html.push_str(&format!(
-            r#"<button class="{}" data-lang="{}-{}"><i class="langtabs-icon {}"></i>{}</button>"#,
+            r#"<button class="{}" data-lang="{}-{}-{}" data-key="{}"{}>{}{}</button>"#,
             class,
-            section.language.to_identifier(),
+            escape_html(&options.id),
+            escape_html(&section.language.id),
             i,
-            section.language.icon_class(),
-            section.language.display_name(),
+            escape_html(&section.key()),
+            extra_attributes,
+            icon,
+            label,
         ));
     }
+    if options.view_toggle {
+        html.push_str(r#"<div class="langtabs-view-toggle" role="group" aria-label="View">"#);
+        for (view, title) in [
+            (View::Tabbed, "Tabs"),
+            (View::Stacked, "Stacked"),
+            (View::Compare, "Compare"),
+        ] {
+            html.push_str(&format!(
+                r#"<button data-view="{}" aria-pressed="{}">{}</button>"#,
+                view.name(),
+                view == options.view,
+                title
+            ));
+        }
+        html.push_str("</div>");
+    }
+    if config.language_filter == LanguageFilter::Gear && options.namespace == DEFAULT_NAMESPACE {
+        html.push_str(
+            r#"<button class="langtabs-filter-toggle" title="Choose languages" aria-label="Choose languages" aria-haspopup="true">⚙</button>"#,
+        );
+    }
     html.push_str("</div>");
 
     // Generate tab content with raw markdown
     html.push_str(r#"<div class="langtabs-content">"#);
     html.push_str("\n\n");
     for (i, section) in sections.iter().enumerate() {
-        let class = if i == 0 {
-            "langtabs-code active"
+        let mut class = if i == active {
+            "langtabs-code active".to_string()
         } else {
-            "langtabs-code"
+            "langtabs-code".to_string()
         };
+        if printed.as_ref().is_some_and(|printed| printed[i]) {
+            class.push_str(" langtabs-print");
+        }
 
         // Create a div with the markdown content inside
         html.push_str("\n\n");
         html.push_str(&format!(
-            r#"<div class="{}" data-lang="{}-{}">"#,
+            r#"<div class="{}" data-lang="{}-{}-{}">"#,
             class,
-            section.language.to_identifier(),
+            escape_html(&options.id),
+            escape_html(&section.language.id),
             i
         ));
+        if headings {
+            html.push_str(&format!(
+                r#"<div class="langtabs-code-heading">{}</div>"#,
+                escape_html(&section.display_name())
+            ));
+        }
+        // Hidden from readers, but indexed by mdBook's search
+        if config.search_tags && !section.placeholder {
+            html.push_str(&format!(
+                r#"<span class="langtabs-search-tag" hidden>({})</span>"#,
+                escape_html(&section.display_name())
+            ));
+        }
         html.push_str("\n\n");
 
         // Insert the raw markdown content
-        html.push_str(&section.content);
+        html.push_str(&section.highlighted_content());
 
         html.push_str("\n\n</div>");
     }
@@ -158,3 +855,513 @@ fn generate_tabs_html(sections: &[LanguageSection]) -> String {
 
     html
 }
+
+// An icon class, or an image file in the book such as `images/acme.svg`
+fn icon_html(
+    section: &LanguageSection,
+    icon: &str,
+    config: &LangTabsConfig,
+    path_to_root: &str,
+) -> String {
+    let lower = icon.to_lowercase();
+    if IMAGE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+        if icon.starts_with('/') || icon.contains("://") || path_to_root.is_empty() {
+            return format!(
+                r#"<img class="langtabs-icon langtabs-icon-image" src="{}" alt="">"#,
+                escape_html(icon)
+            );
+        }
+        // mdBook doesn't rewrite raw HTML for the print page at the book root, so
+        // `langtabs.js` resolves the path from the root for whichever page shows it
+        return format!(
+            r#"<img class="langtabs-icon langtabs-icon-image" src="{}{}" data-root-src="{}" alt="">"#,
+            path_to_root,
+            escape_html(icon),
+            escape_html(icon)
+        );
+    }
+
+    if config.icons == IconSource::Bundled {
+        let svg = bundled_svg(section, icon, config)
+            .unwrap_or_else(|| monogram_svg(&section.display_name()));
+        return format!(
+            r#"<span class="langtabs-icon langtabs-icon-svg" aria-hidden="true">{}</span>"#,
+            svg
+        );
+    }
+
+    format!(
+        r#"<i class="langtabs-icon {}" aria-hidden="true"></i>"#,
+        escape_html(icon)
+    )
+}
+
+// `<key>.svg` or, for Devicon classes, e.g. `rust-plain.svg` from the icon directory,
+// then the badge embedded for the language
+fn bundled_svg(section: &LanguageSection, icon: &str, config: &LangTabsConfig) -> Option<String> {
+    let name = icon.trim_start_matches("devicon-");
+    [section.key().as_str(), name]
+        .iter()
+        .find_map(|key| config.icon_files.get(*key).cloned())
+        .or_else(|| icons::bundled(&section.language.id).map(String::from))
+}
+
+// A badge with the first letters of the name, for languages without an icon file
+fn monogram_svg(name: &str) -> String {
+    let letters: String = name
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take(2)
+        .collect();
+    format!(
+        concat!(
+            r#"<svg viewBox="0 0 24 24" width="1em" height="1em" role="img">"#,
+            r#"<rect x="1" y="1" width="22" height="22" rx="5" fill="none" stroke="currentColor" stroke-width="2"/>"#,
+            r#"<text x="12" y="16" text-anchor="middle" font-size="10" font-family="sans-serif" font-weight="bold" fill="currentColor">{}</text>"#,
+            "</svg>"
+        ),
+        escape_html(&letters)
+    )
+}
+
+// Render a group as its code blocks one after another, for renderers without script
+fn generate_sequential(sections: &[LanguageSection], style: OutputStyle) -> String {
+    sections
+        .iter()
+        .map(|section| match style {
+            OutputStyle::Headings => {
+                format!("**{}**\n\n{}", section.display_name(), section.content)
+            }
+            _ => section.content.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// Operating system a tab in an OS group stands for, e.g. "ubuntu" -> "linux"
+fn operating_system<'a>(key: &str, config: &'a LangTabsConfig) -> Option<&'a str> {
+    config
+        .os_labels
+        .iter()
+        .find(|(os, labels)| slugify(os) == key || labels.iter().any(|label| slugify(label) == key))
+        .map(|(os, _)| os.as_str())
+}
+
+/// Turn a tab label into a key, e.g. "macOS 14" -> "macos-14".
+pub fn slugify(label: &str) -> String {
+    label
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Keep markdown from opening HTML tags while leaving code spans alone, since
+// entities aren't decoded inside them, e.g. "`Vec<T>` or <b>" -> "`Vec<T>` or &lt;b>"
+fn escape_markdown_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['`', '<']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with('<') {
+            result.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        }
+
+        // A code span closes with a run of as many backticks as it opened with
+        let run = rest.len() - rest.trim_start_matches('`').len();
+        let fence = &rest[..run];
+        let mut end = None;
+        let mut search = run;
+        while let Some(offset) = rest[search..].find(fence) {
+            let close = search + offset;
+            let close_run = rest[close..].len() - rest[close..].trim_start_matches('`').len();
+            if close_run == run {
+                end = Some(close + run);
+                break;
+            }
+            search = close + close_run;
+        }
+        let end = end.unwrap_or(run);
+        result.push_str(&rest[..end]);
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::LanguageRegistry;
+
+    const GROUP: &str = "<!-- langtabs-start -->
+```rust,should_panic
+panic!();
+```
+```py
+print(1)
+```
+<!-- langtabs-end -->
+";
+
+    fn render(content: &str, config: &LangTabsConfig, style: OutputStyle) -> String {
+        let location = ChapterLocation {
+            path_to_root: String::new(),
+            slug: "intro".to_string(),
+        };
+        let mut problems = Vec::new();
+        let output = process_markdown(
+            content,
+            config,
+            &config.page_config(Vec::new()),
+            style,
+            &location,
+            &mut problems,
+        );
+        assert!(problems.is_empty(), "unexpected problems: {:?}", problems);
+        output
+    }
+
+    #[test]
+    fn plain_output_keeps_code_blocks_as_written() {
+        let output = render(GROUP, &LangTabsConfig::default(), OutputStyle::Plain);
+        assert_eq!(
+            output.trim_end(),
+            "```rust,should_panic\npanic!();\n```\n\n```py\nprint(1)\n```"
+        );
+    }
+
+    #[test]
+    fn tabs_keep_fence_attributes() {
+        let content = GROUP.replace("```py", "```rust editable");
+        let output = render(&content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains("\n```rust,should_panic\npanic!();\n```\n"));
+        assert!(output.contains("\n```rust editable\nprint(1)\n```\n"));
+    }
+
+    #[test]
+    fn only_regions_in_code_are_left_alone() {
+        let content = "<!-- langtabs-only rust -->
+Rust only.
+<!-- langtabs-only-end -->
+
+```markdown
+<!-- langtabs-only go -->
+Go only.
+<!-- langtabs-only-end -->
+```
+";
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"<div class="langtabs-only" data-langs="rust">"#));
+        assert!(!output.contains(r#"data-langs="go""#));
+        assert!(output.contains("```markdown\n<!-- langtabs-only go -->\nGo only.\n"));
+    }
+
+    #[test]
+    fn terms_keep_code_spans_as_written() {
+        let content = r#"Pass {{lt rust="`&str` or <b>" java="`List<String>`"}}."#;
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"data-lang="rust">`&str` or &lt;b><span"#));
+        assert!(output.contains(r#"data-lang="java">`List<String>`<span"#));
+
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Headings);
+        assert_eq!(
+            output,
+            "Pass `&str` or &lt;b> (Rust) / `List<String>` (Java)."
+        );
+    }
+
+    #[test]
+    fn terms_in_code_are_left_alone() {
+        let content = r#"Write `{{lt rust="a" js="b"}}` for {{lt rust="`a`" js="`b`"}}.
+
+```markdown
+{{lt rust="a" js="b"}}
+```
+"#;
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(
+            output
+                .starts_with(r#"Write `{{lt rust="a" js="b"}}` for <span class="langtabs-term">"#)
+        );
+        assert!(output.contains("```markdown\n{{lt rust=\"a\" js=\"b\"}}\n```"));
+    }
+
+    fn section(config: &LangTabsConfig, language: &str, label: Option<&str>) -> LanguageSection {
+        let mut attributes = BTreeMap::new();
+        if let Some(label) = label {
+            attributes.insert("label".to_string(), label.to_string());
+        }
+        LanguageSection::from_code_block(config.language(language), &mut attributes, &[])
+    }
+
+    #[test]
+    fn bundled_icons_prefer_files_over_badges() {
+        let mut config = LangTabsConfig::default();
+        let rust = section(&config, "rust", None);
+        let badge = bundled_svg(&rust, "devicon-rust-plain", &config).unwrap();
+        assert!(badge.contains(">Rs</text>"));
+
+        config
+            .icon_files
+            .insert("rust-plain".to_string(), "<svg>devicon</svg>".to_string());
+        assert_eq!(
+            bundled_svg(&rust, "devicon-rust-plain", &config).as_deref(),
+            Some("<svg>devicon</svg>")
+        );
+
+        config
+            .icon_files
+            .insert("rust".to_string(), "<svg>key</svg>".to_string());
+        assert_eq!(
+            bundled_svg(&rust, "devicon-rust-plain", &config).as_deref(),
+            Some("<svg>key</svg>")
+        );
+
+        // Languages without a badge fall back to a monogram in `icon_html`
+        let sql = section(&config, "sql", None);
+        assert_eq!(bundled_svg(&sql, "devicon-devicon-plain", &config), None);
+    }
+
+    #[test]
+    fn required_languages_get_placeholders() {
+        let config = LangTabsConfig {
+            required: vec!["go".to_string()],
+            ..LangTabsConfig::default()
+        };
+        let output = render(GROUP, &config, OutputStyle::Tabs);
+        assert!(output.contains(r#"<button class="langtabs-tab langtabs-placeholder" data-lang="langtabs-intro-0-go-2" data-key="go""#));
+        assert!(output.contains("The Go example is coming soon."));
+    }
+
+    #[test]
+    fn languages_are_collected_in_order_of_appearance() {
+        let content = format!(
+            "{}\n<!-- langtabs-start namespace=\"os\" -->\n```sh\nls\n```\n<!-- langtabs-end -->\n{}",
+            GROUP,
+            GROUP.replace("```py", "```go")
+        );
+        let mut languages = Vec::new();
+        collect_languages(&content, &LangTabsConfig::default(), &mut languages);
+
+        let languages: Vec<(&str, &str)> = languages
+            .iter()
+            .map(|language| (language.key.as_str(), language.name.as_str()))
+            .collect();
+        assert_eq!(
+            languages,
+            [("rust", "Rust"), ("python", "Python"), ("go", "Go")]
+        );
+    }
+
+    #[test]
+    fn terms_expand_per_style() {
+        let content = r#"Call {{lt rust="`new()`" py="`create()`"}} first."#;
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"<span class="langtabs-term-variant" data-lang="rust">`new()`"#));
+        assert!(
+            output.contains(r#"<span class="langtabs-term-variant" data-lang="python">`create()`"#)
+        );
+
+        let output = render(content, &LangTabsConfig::default(), OutputStyle::Headings);
+        assert_eq!(output, "Call `new()` (Rust) / `create()` (Python) first.");
+    }
+
+    #[test]
+    fn tab_labels_map_to_operating_systems() {
+        let config = LangTabsConfig::default();
+        assert_eq!(operating_system("ubuntu", &config), Some("linux"));
+        assert_eq!(operating_system("macos", &config), Some("macos"));
+        assert_eq!(operating_system("os-x", &config), Some("macos"));
+        assert_eq!(operating_system("windows", &config), Some("windows"));
+        assert_eq!(operating_system("freebsd", &config), None);
+    }
+
+    #[test]
+    fn labelled_tabs_follow_order_and_default_tab() {
+        let content = r#"<!-- langtabs-start namespace="os" -->
+<!-- langtabs-tab label="Ubuntu" -->
+```sh
+apt install foo
+```
+<!-- langtabs-tab label="macOS" -->
+```sh
+brew install foo
+```
+<!-- langtabs-end -->
+"#;
+        let config = LangTabsConfig {
+            order: vec!["macOS".to_string(), "Ubuntu".to_string()],
+            default_tab: Some("Ubuntu".to_string()),
+            ..LangTabsConfig::default()
+        };
+        let output = render(content, &config, OutputStyle::Tabs);
+        let macos = output.find(r#"data-key="macos""#).unwrap();
+        let ubuntu = output.find(r#"data-key="ubuntu""#).unwrap();
+        assert!(macos < ubuntu);
+        assert!(output.contains(r#""defaultTab":"ubuntu""#));
+    }
+
+    #[test]
+    fn highlight_as_rewrites_the_fence() {
+        let mut config = LangTabsConfig::default();
+        config.languages.insert(
+            "acme".to_string(),
+            serde_json::from_str(r#"{"highlight-as": "terraform"}"#).unwrap(),
+        );
+        config.registry = LanguageRegistry::with_custom(&config.languages);
+
+        let content = GROUP.replace("```py", "```acme editable");
+        let output = render(&content, &config, OutputStyle::Tabs);
+        assert!(output.contains("\n```hcl editable\nprint(1)\n```\n"));
+    }
+
+    #[test]
+    fn fences_keep_the_name_they_were_written_with() {
+        let content = GROUP
+            .replace("```rust,should_panic", "```sh")
+            .replace("```py", "```pyi");
+        let output = render(&content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains("\n```sh\npanic!();\n```\n"));
+        assert!(output.contains("\n```pyi\nprint(1)\n```\n"));
+        assert!(output.contains(r#"data-key="shell""#));
+        assert!(output.contains(r#"data-key="python""#));
+
+        // Extensions shared by several languages don't pick one
+        assert!(!LangTabsConfig::default().language("conf").known);
+    }
+
+    #[test]
+    fn image_icons_resolve_from_the_book_root() {
+        let config = LangTabsConfig::default();
+        let rust = section(&config, "rust", None);
+
+        assert_eq!(
+            icon_html(&rust, "images/rust.svg", &config, ""),
+            r#"<img class="langtabs-icon langtabs-icon-image" src="images/rust.svg" alt="">"#
+        );
+        assert_eq!(
+            icon_html(&rust, "images/rust.svg", &config, "../../"),
+            r#"<img class="langtabs-icon langtabs-icon-image" src="../../images/rust.svg" data-root-src="images/rust.svg" alt="">"#
+        );
+        assert_eq!(
+            icon_html(&rust, "https://example.com/rust.png", &config, "../"),
+            r#"<img class="langtabs-icon langtabs-icon-image" src="https://example.com/rust.png" alt="">"#
+        );
+        assert_eq!(
+            icon_html(&rust, "devicon-rust-plain", &config, "../"),
+            r#"<i class="langtabs-icon devicon-rust-plain" aria-hidden="true"></i>"#
+        );
+    }
+
+    #[test]
+    fn every_html_page_gets_the_config() {
+        let output = render("# Intro\n", &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"<script type="application/json" class="langtabs-config">"#));
+    }
+
+    fn problems(content: &str) -> Vec<String> {
+        let config = LangTabsConfig::default();
+        let location = ChapterLocation {
+            path_to_root: String::new(),
+            slug: "intro".to_string(),
+        };
+        let mut problems = Vec::new();
+        process_markdown(
+            content,
+            &config,
+            &config.page_config(Vec::new()),
+            OutputStyle::Tabs,
+            &location,
+            &mut problems,
+        );
+        problems
+    }
+
+    #[test]
+    fn layouts_are_validated() {
+        let output = render(
+            &GROUP.replace("langtabs-start", r#"langtabs-start layout="pills""#),
+            &LangTabsConfig::default(),
+            OutputStyle::Tabs,
+        );
+        assert!(output.contains(r#"class="langtabs langtabs-pills""#));
+
+        let problems =
+            problems(&GROUP.replace("langtabs-start", r#"langtabs-start layout="grid""#));
+        assert_eq!(
+            problems,
+            ["unknown layout `grid`, expected one of: tabs, pills, vertical, auto"]
+        );
+    }
+
+    #[test]
+    fn views_are_validated() {
+        let output = render(
+            &GROUP.replace(
+                "langtabs-start",
+                r#"langtabs-start view="compare" view-toggle="true""#,
+            ),
+            &LangTabsConfig::default(),
+            OutputStyle::Tabs,
+        );
+        assert!(output.contains(r#"data-view="compare""#));
+        assert!(output.contains(r#"<div class="langtabs-code-heading">Python</div>"#));
+
+        let problems = problems(&GROUP.replace("langtabs-start", r#"langtabs-start view="grid""#));
+        assert_eq!(
+            problems,
+            ["unknown view `grid`, expected one of: tabbed, stacked, compare"]
+        );
+    }
+
+    #[test]
+    fn listed_languages_are_printed() {
+        let config = LangTabsConfig {
+            print: Print::Languages(vec!["python".to_string()]),
+            ..LangTabsConfig::default()
+        };
+        let output = render(GROUP, &config, OutputStyle::Tabs);
+        assert!(output.contains("langtabs-print-stacked"));
+        assert!(output.contains(
+            r#"<div class="langtabs-code langtabs-print" data-lang="langtabs-intro-0-python-1">"#
+        ));
+
+        // Groups without a listed language print their selected tab
+        let config = LangTabsConfig {
+            print: Print::Languages(vec!["go".to_string()]),
+            ..LangTabsConfig::default()
+        };
+        let output = render(GROUP, &config, OutputStyle::Tabs);
+        assert!(!output.contains("langtabs-print"));
+    }
+
+    #[test]
+    fn group_ids_include_the_chapter() {
+        let output = render(GROUP, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"data-group="langtabs-intro-0""#));
+
+        let content = GROUP.replace("langtabs-start", r#"langtabs-start id="install""#);
+        let output = render(&content, &LangTabsConfig::default(), OutputStyle::Tabs);
+        assert!(output.contains(r#"data-group="langtabs-intro-install""#));
+    }
+
+    #[test]
+    fn headings_output_names_each_block() {
+        let output = render(GROUP, &LangTabsConfig::default(), OutputStyle::Headings);
+        assert!(output.contains("**Rust**\n\n```rust,should_panic\n"));
+        assert!(output.contains("**Python**\n\n```py\n"));
+        assert!(!output.contains("langtabs-config"));
+    }
+}